@@ -7,14 +7,21 @@ fn main() {
     // include/redismodule.h is just vendored in from the Redis project and
     // src/redismodule.c is just a stub that includes it and plays a few other
     // tricks that we need to complete the build.
+    //
+    // Both files include the header, which declares every API function
+    // pointer without `extern`. Compilers default to `-fno-common` nowadays,
+    // which turns those into duplicate symbols as soon as the objects are
+    // linked into a module, so have them merged as they used to be.
     cc::Build::new()
         .file("src/redismodule.c")
         .include("include/")
+        .flag_if_supported("-fcommon")
         .compile("libredismodule.a");
 
     cc::Build::new()
         .file("src/redis_mod_callable.c")
         .include("include/")
+        .flag_if_supported("-fcommon")
         .compile("libredis_mod_callable.a");
 }
 
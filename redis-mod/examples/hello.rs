@@ -0,0 +1,2515 @@
+//! A minimal module, built as `libhello.so` with `cargo build --example hello`
+//! and loaded with `redis-server --loadmodule`.
+//!
+//! It's also the module the integration tests under tests/ run against.
+
+#[macro_use]
+extern crate redis_mod;
+
+use libc::c_int;
+use redis_mod::redis::{
+    BeginSearch, CommandFilter, CommandFilterCtx, DataType, FindKeys, InfoContext, KeySpecBuilder,
+    KeyType, LogLevel, ModuleIO, ModuleType, OpenKeyFlags, Redis, Reply, ThreadSafeContext,
+    TypeRegistry, WorkerPool,
+};
+use redis_mod::redis::ConfigBuilder;
+use redis_mod::{raw, Command, RModError, RedisModuleInitializer};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+
+const MODULE_NAME: &str = "hello";
+const MODULE_VERSION: c_int = 1;
+
+// The arguments the module was loaded with.
+static ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+// The `hello.limit` config, replied by HELLO.LIMIT.
+static LIMIT: AtomicI64 = AtomicI64::new(10);
+
+// The `proto-max-bulk-len` the server was started with, which HELLO.SETRANGE
+// doesn't let strings grow past.
+static MAX_BULK_LEN: OnceLock<usize> = OnceLock::new();
+
+// The workers running the jobs queued by HELLO.BGINCR.
+static POOL: OnceLock<WorkerPool> = OnceLock::new();
+
+// How many times `hello.limit` was applied, replied by HELLO.APPLIED.
+static LIMIT_APPLIED: AtomicI64 = AtomicI64::new(0);
+
+fn init(initializer: &RedisModuleInitializer, args: &[&str]) -> Result<(), RModError> {
+    let _ = ARGS.set(args.iter().map(|arg| arg.to_string()).collect());
+    initializer.register_cluster_message_receiver(HELLO_MESSAGE, receive_hello);
+    initializer.register_info_func(hello_info)?;
+    initializer.register_type(ModuleType::<Tally>::builder(TALLY_TYPE))?;
+    let _ = POOL.set(initializer.worker_pool(2, 64)?);
+    let _ = MAX_BULK_LEN.set(initializer.proto_max_bulk_len()?);
+    let configs = ConfigBuilder::new()
+        .numeric(
+            "limit",
+            10,
+            1,
+            1000,
+            raw::ConfigFlags::DEFAULT,
+            |_| LIMIT.load(Ordering::Relaxed),
+            |_, limit| {
+                LIMIT.store(limit, Ordering::Relaxed);
+                Ok(())
+            },
+        )
+        .on_apply(|_| {
+            LIMIT_APPLIED.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        });
+    initializer.load_configs(configs)?;
+    initializer.register_command_filter(AliasFilter, raw::CommandFilterFlags::NOSELF)
+}
+
+// Adds a `hello_stats` section to `INFO`.
+fn hello_info(ctx: &mut InfoContext, _for_crash_report: bool) {
+    if ctx.add_section("stats").is_err() {
+        return;
+    }
+    let loaded_args = ARGS.get().map_or(0, Vec::len);
+    let _ = ctx.add_field_long_long("loaded_args", loaded_args as i64);
+}
+
+// The name of the custom type of the keys incremented by HELLO.TALLY.
+const TALLY_TYPE: &str = "hellotaly";
+
+// The value of the keys incremented by HELLO.TALLY.
+struct Tally(u64);
+
+impl DataType for Tally {
+    fn rdb_load(io: &mut ModuleIO, _encver: i32) -> Result<Tally, RModError> {
+        Ok(Tally(io.load_unsigned()))
+    }
+
+    fn rdb_save(&self, io: &mut ModuleIO) {
+        io.save_unsigned(self.0);
+    }
+}
+
+// The type of the cluster messages sent by HELLO.BROADCAST.
+const HELLO_MESSAGE: u8 = 1;
+
+fn receive_hello(r: &Redis, sender_id: &str, _msg_type: u8, payload: &[u8]) {
+    let message = format!("{} says {}", sender_id, String::from_utf8_lossy(payload));
+    r.log(LogLevel::Notice, &message);
+}
+
+// Rewrites `GET @name` into `GET hello:name`, so that clients can read the
+// module's keys without spelling out its prefix.
+struct AliasFilter;
+
+impl CommandFilter for AliasFilter {
+    fn filter(&self, ctx: &mut CommandFilterCtx) {
+        if ctx.arg_count() != 2 {
+            return;
+        }
+        match ctx.arg_get(0) {
+            Ok(Some(cmd)) if cmd.eq_ignore_ascii_case("get") => (),
+            _ => return,
+        }
+        if let Ok(Some(key)) = ctx.arg_get(1) {
+            if let Some(name) = key.strip_prefix('@') {
+                let _ = ctx.arg_replace(1, &format!("hello:{}", name));
+            }
+        }
+    }
+}
+
+// HELLO.ECHO message
+//
+// Replies with the given message.
+struct EchoCommand;
+
+impl Command for EchoCommand {
+    fn name(&self) -> &'static str {
+        "hello.echo"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_string(args[1])
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.INCR key
+//
+// Increments the integer at `key` through `INCR` and replies with the new
+// value.
+struct IncrCommand;
+
+impl Command for IncrCommand {
+    fn name(&self) -> &'static str {
+        "hello.incr"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let reply = r.call_replicated("incr", &[args[1]])?;
+        r.reply_integer(reply.to_integer()?)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.GET key
+//
+// Replies with the string at `key`, or nil if it doesn't exist.
+struct GetCommand;
+
+impl Command for GetCommand {
+    fn name(&self) -> &'static str {
+        "hello.get"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key(args[1]);
+        match key.read()? {
+            Some(val) => r.reply_string(&val),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.CLIENT
+//
+// Replies with the id and address of the calling client.
+struct ClientCommand;
+
+impl Command for ClientCommand {
+    fn name(&self) -> &'static str {
+        "hello.client"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        let info = r.client_info()?;
+        r.reply_array(2)?;
+        r.reply_integer(r.client_id() as i64)?;
+        r.reply_string(&info.addr)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.WORDS message
+//
+// Replies with the whitespace separated words of the message.
+struct WordsCommand;
+
+impl Command for WordsCommand {
+    fn name(&self) -> &'static str {
+        "hello.words"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let mut array = r.reply_array_deferred()?;
+        for word in args[1].split_whitespace() {
+            array.reply_element(|r| r.reply_string(word))?;
+        }
+        array.finish();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.README
+//
+// Replies with a short markdown text about the module.
+struct ReadmeCommand;
+
+impl Command for ReadmeCommand {
+    fn name(&self) -> &'static str {
+        "hello.readme"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        r.reply_verbatim_string("mkd", b"# hello\nAn example module.")
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.BIG digits
+//
+// Replies with the digits as a big number.
+struct BigCommand;
+
+impl Command for BigCommand {
+    fn name(&self) -> &'static str {
+        "hello.big"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_big_number(args[1])
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.KEYS [pattern]
+//
+// Replies with the names of the keys matching the pattern, or all of them.
+struct KeysCommand;
+
+impl Command for KeysCommand {
+    fn name(&self) -> &'static str {
+        "hello.keys"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let keys = r.scan_all(args.get(1).copied())?;
+        r.reply_array(keys.len() as i64)?;
+        for key in &keys {
+            r.reply_string(key)?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn arity(&self) -> i32 {
+        -1
+    }
+}
+
+// HELLO.HFIELDS key
+//
+// Replies with the number of fields of the hash at `key`.
+struct HfieldsCommand;
+
+impl Command for HfieldsCommand {
+    fn name(&self) -> &'static str {
+        "hello.hfields"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let mut count = 0;
+        key.hscan(|_field, _value| count += 1)?;
+        r.reply_integer(count)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.GETRANGE key start end
+//
+// Replies with part of the string at `key`, like `GETRANGE`.
+struct GetrangeCommand;
+
+impl Command for GetrangeCommand {
+    fn name(&self) -> &'static str {
+        "hello.getrange"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let start = args[2].parse::<i64>()?;
+        let end = args[3].parse::<i64>()?;
+        r.reply_string(&key.get_range(start, end)?)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.SETRANGE key offset value
+//
+// Overwrites part of the string at `key`, like `SETRANGE`.
+struct SetrangeCommand;
+
+impl Command for SetrangeCommand {
+    fn name(&self) -> &'static str {
+        "hello.setrange"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let offset = args[2].parse::<usize>()?;
+        let max_len = *MAX_BULK_LEN.get().ok_or_else(|| error!("No maximum string length"))?;
+        let len = key.set_range(offset, args[3].as_bytes(), max_len)?;
+        r.replicate_verbatim();
+        r.reply_integer(len as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.LRANGE key start stop
+//
+// Replies with a range of the list at `key`, like `LRANGE`.
+struct LrangeCommand;
+
+impl Command for LrangeCommand {
+    fn name(&self) -> &'static str {
+        "hello.lrange"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let elements = key.lrange(args[2].parse::<i64>()?, args[3].parse::<i64>()?)?;
+        r.reply_array(elements.len() as i64)?;
+        for ele in &elements {
+            r.reply_string(ele)?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.LINDEX key index
+//
+// Replies with the element of the list at `key` at the index, like `LINDEX`.
+struct LindexCommand;
+
+impl Command for LindexCommand {
+    fn name(&self) -> &'static str {
+        "hello.lindex"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        match key.lindex(args[2].parse::<i64>()?)? {
+            Some(ele) => r.reply_string(&ele),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.LSET key index element
+//
+// Replaces the element of the list at `key` at the index, like `LSET`.
+struct LsetCommand;
+
+impl Command for LsetCommand {
+    fn name(&self) -> &'static str {
+        "hello.lset"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        key.lset(args[2].parse::<i64>()?, args[3])?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.LINSERT key index element
+//
+// Inserts the element into the list at `key` so that it ends up at the index.
+struct LinsertCommand;
+
+impl Command for LinsertCommand {
+    fn name(&self) -> &'static str {
+        "hello.linsert"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        key.linsert(args[2].parse::<i64>()?, args[3])?;
+        r.replicate_verbatim();
+        r.reply_integer(key.cardinality()? as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.RELOAD key
+//
+// Dumps the value at `key`, deletes it and restores it from the dump.
+struct ReloadCommand;
+
+impl Command for ReloadCommand {
+    fn name(&self) -> &'static str {
+        "hello.reload"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let payload = {
+            let key = r.open_key_writable(args[1]);
+            let payload = key.dump()?;
+            key.delete()?;
+            payload
+        };
+        r.restore(args[1], &payload, None)?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.STRLEN key
+//
+// Replies with the length in bytes of the string at `key`, read through `GET`
+// so that it works for binary values.
+struct StrlenCommand;
+
+impl Command for StrlenCommand {
+    fn name(&self) -> &'static str {
+        "hello.strlen"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let reply = r.call("get", &[args[1]])?;
+        r.reply_integer(reply.to_bytes()?.len() as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.HEX key
+//
+// Replies with the string at `key` in hexadecimal, or nil if it doesn't
+// exist.
+struct HexCommand;
+
+impl Command for HexCommand {
+    fn name(&self) -> &'static str {
+        "hello.hex"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key(args[1]);
+        match key.read_bytes()? {
+            Some(bytes) => r.reply_string(&bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.INSPECT message
+//
+// Replies with the message as Redis stores it and its length in bytes.
+struct InspectCommand;
+
+impl Command for InspectCommand {
+    fn name(&self) -> &'static str {
+        "hello.inspect"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let message = r.create_string(args[1]);
+        r.reply_array(3)?;
+        r.reply_string(&message.to_string_lossy())?;
+        r.reply_string(&format!("<{}>", message))?;
+        r.reply_integer(message.as_bytes().len() as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.CLUSTER
+//
+// Replies with the node id of the server, or nil outside of a cluster, and
+// the number of nodes in the cluster.
+struct ClusterCommand;
+
+impl Command for ClusterCommand {
+    fn name(&self) -> &'static str {
+        "hello.cluster"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        r.reply_array(2)?;
+        match r.cluster_my_id() {
+            Some(id) => r.reply_string(&id)?,
+            None => r.reply_null(),
+        }
+        r.reply_integer(r.cluster_size() as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.BROADCAST message
+//
+// Sends the message to the other nodes of the cluster, which log it.
+struct BroadcastCommand;
+
+impl Command for BroadcastCommand {
+    fn name(&self) -> &'static str {
+        "hello.broadcast"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.send_cluster_message(None, HELLO_MESSAGE, args[1].as_bytes())?;
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.HSET key field value
+//
+// Sets the field of the hash at `key` through the module API, firing a
+// `hello.hset` keyspace event.
+struct HsetCommand;
+
+impl Command for HsetCommand {
+    fn name(&self) -> &'static str {
+        "hello.hset"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        key.rm_hset(args[2], args[3])?;
+        r.notify_keyspace_event(raw::NotifyFlags::HASH, "hello.hset", args[1])?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.INCRBY key increment
+//
+// Increments the integer at `key` through the module API, like `INCRBY`.
+struct IncrbyCommand;
+
+impl Command for IncrbyCommand {
+    fn name(&self) -> &'static str {
+        "hello.incrby"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let val = key.incr_by(args[2].parse::<i64>()?)?;
+        r.replicate_verbatim();
+        r.reply_integer(val)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.GETSET key value
+//
+// Sets the string at `key` and replies with the previous one, like `GETSET`.
+struct GetsetCommand;
+
+impl Command for GetsetCommand {
+    fn name(&self) -> &'static str {
+        "hello.getset"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let old = key.get_set(args[2])?;
+        r.replicate_verbatim();
+        match old {
+            Some(val) => r.reply_string(&val),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.TIME
+//
+// Replies with the UNIX time in milliseconds and a monotonic time in
+// microseconds.
+struct TimeCommand;
+
+impl Command for TimeCommand {
+    fn name(&self) -> &'static str {
+        "hello.time"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        let monotonic = r.monotonic_micros()?;
+        r.reply_array(2)?;
+        r.reply_integer(r.now_ms())?;
+        r.reply_integer(monotonic as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.RANDOM
+//
+// Replies with a random key of the selected database, or null if it's empty.
+struct RandomCommand;
+
+impl Command for RandomCommand {
+    fn name(&self) -> &'static str {
+        "hello.random"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        match r.random_key()? {
+            Some(key) => r.reply_string(&key),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly random"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.DBSIZE
+//
+// Replies with the number of keys in the selected database.
+struct DbSizeCommand;
+
+impl Command for DbSizeCommand {
+    fn name(&self) -> &'static str {
+        "hello.dbsize"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(r.db_size()?)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.XADD
+//
+// Appends an entry with the given field/value pairs to the stream at the key
+// and replies with its ID, like XADD with an auto-generated ID.
+struct XaddCommand;
+
+impl Command for XaddCommand {
+    fn name(&self) -> &'static str {
+        "hello.xadd"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        if args.len() % 2 != 0 {
+            return Err(error!("Usage: {} <key> <field> <value> [<field> <value> ...]", self.name()));
+        }
+        let key = r.open_key_writable(args[1]);
+        let fields: Vec<(&str, &str)> = args[2..].chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        let id = key.xadd(&fields)?;
+        r.replicate_verbatim();
+        r.reply_string(&id)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        -4
+    }
+}
+
+// HELLO.XRANGE
+//
+// Replies with the entries of the stream at the key between two IDs, like
+// XRANGE.
+struct XrangeCommand;
+
+impl Command for XrangeCommand {
+    fn name(&self) -> &'static str {
+        "hello.xrange"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        let entries = key.xrange(args[2], args[3])?;
+        r.reply_array(entries.len() as i64)?;
+        for (id, fields) in entries {
+            r.reply_array(2)?;
+            r.reply_string(&id)?;
+            r.reply_array(fields.len() as i64 * 2)?;
+            for (field, value) in fields {
+                r.reply_string(&field)?;
+                r.reply_string(&value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.PEEK
+//
+// Replies with the string value of the key like HELLO.GET, but without
+// counting as an access to the key.
+struct PeekCommand;
+
+impl Command for PeekCommand {
+    fn name(&self) -> &'static str {
+        "hello.peek"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_with_flags(args[1], OpenKeyFlags::NOTOUCH);
+        match key.read()? {
+            Some(val) => r.reply_string(&val),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.IDLE
+//
+// Replies with the idle time of the key in seconds, or with its access
+// frequency instead if an LFU eviction policy is in use.
+struct IdleCommand;
+
+impl Command for IdleCommand {
+    fn name(&self) -> &'static str {
+        "hello.idle"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key(args[1]);
+        match key.idle_time() {
+            Ok(idle) => r.reply_integer(idle as i64),
+            Err(_) => r.reply_integer(key.access_freq()? as i64),
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.HALFWAY
+//
+// Replies with its argument and then fails, to show that the failure isn't
+// replied on top of the reply already sent.
+struct HalfwayCommand;
+
+impl Command for HalfwayCommand {
+    fn name(&self) -> &'static str {
+        "hello.halfway"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_string(args[1])?;
+        Err(error!("Failed after replying"))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.PARTIAL
+//
+// Tries to reply with an array holding a value that can't be replied, to
+// show that the error is replied instead of a truncated array.
+struct PartialCommand;
+
+impl Command for PartialCommand {
+    fn name(&self) -> &'static str {
+        "hello.partial"
+    }
+
+    fn run_reply(&self, _r: Redis, _args: &[&str]) -> Result<Reply, RModError> {
+        Ok(Reply::Array(vec![
+            Reply::Integer(1),
+            Reply::Map(vec![(Reply::String("key".to_string()), Reply::Unknown)]),
+        ]))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.RENAME key newkey
+//
+// Renames a key, like `RENAME`.
+struct RenameCommand;
+
+impl Command for RenameCommand {
+    fn name(&self) -> &'static str {
+        "hello.rename"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.rename_key(args[1], args[2])?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.SETIN db key value
+//
+// Sets a string key in another database, switching back to the client's own
+// database afterwards.
+struct SetinCommand;
+
+impl Command for SetinCommand {
+    fn name(&self) -> &'static str {
+        "hello.setin"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let original = r.get_selected_db();
+        r.select_db(args[1].parse::<i32>()?)?;
+        let written = r.open_key_writable(args[2]).write(args[3]);
+        r.select_db(original)?;
+        written?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.TRUNCATE key length
+//
+// Cuts the string at `key` short or pads it with zero bytes to the length.
+struct TruncateCommand;
+
+impl Command for TruncateCommand {
+    fn name(&self) -> &'static str {
+        "hello.truncate"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        key.truncate(args[2].parse::<usize>()?)?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.MARK key value
+//
+// Sets a string key and replicates it as a plain `SET`.
+struct MarkCommand;
+
+impl Command for MarkCommand {
+    fn name(&self) -> &'static str {
+        "hello.mark"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.open_key_writable(args[1]).write(args[2])?;
+        r.replicate("set", &[args[1], args[2]])?;
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.FLAGS
+//
+// Replies with the names of some of the flags of the context the command
+// runs in.
+struct FlagsCommand;
+
+impl Command for FlagsCommand {
+    fn name(&self) -> &'static str {
+        "hello.flags"
+    }
+
+    fn run_reply(&self, r: Redis, _args: &[&str]) -> Result<Reply, RModError> {
+        let flags = r.context_flags();
+        let names = [
+            ("lua", raw::ContextFlags::LUA),
+            ("multi", raw::ContextFlags::MULTI),
+            ("master", raw::ContextFlags::MASTER),
+        ];
+        Ok(Reply::Array(
+            names
+                .iter()
+                .filter(|(_, flag)| flags.contains(*flag))
+                .map(|(name, _)| Reply::String(name.to_string()))
+                .collect(),
+        ))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.LIMIT
+//
+// Replies with the value of the `hello.limit` config.
+struct LimitCommand;
+
+impl Command for LimitCommand {
+    fn name(&self) -> &'static str {
+        "hello.limit"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(LIMIT.load(Ordering::Relaxed))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.TYPE key
+//
+// Replies with the kind of value held by `key`, e.g. `string` or `empty`.
+struct TypeCommand;
+
+impl Command for TypeCommand {
+    fn name(&self) -> &'static str {
+        "hello.type"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key_type = r.open_key(args[1]).key_type();
+        r.reply_string(&format!("{:?}", key_type).to_lowercase())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.HGETALL key
+//
+// Replies with every field and value of the hash at `key`, like `HGETALL`.
+struct HgetallCommand;
+
+impl Command for HgetallCommand {
+    fn name(&self) -> &'static str {
+        "hello.hgetall"
+    }
+
+    fn run_reply(&self, r: Redis, args: &[&str]) -> Result<Reply, RModError> {
+        let pairs = r.open_key_writable(args[1]).rm_hgetall()?;
+        Ok(Reply::Map(
+            pairs
+                .into_iter()
+                .map(|(field, value)| (Reply::String(field), Reply::String(value)))
+                .collect(),
+        ))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.HDEL key field
+//
+// Deletes a field from the hash at `key`, replying whether it existed.
+struct HdelCommand;
+
+impl Command for HdelCommand {
+    fn name(&self) -> &'static str {
+        "hello.hdel"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let deleted = r.open_key_writable(args[1]).rm_hdel(args[2])?;
+        r.replicate_verbatim();
+        r.reply_integer(deleted as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.LATER key value milliseconds
+//
+// Sets a string key once the given time has passed, from a timer.
+struct LaterCommand;
+
+impl Command for LaterCommand {
+    fn name(&self) -> &'static str {
+        "hello.later"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let period = time::Duration::milliseconds(args[3].parse::<i64>()?);
+        let (key, value) = (args[1].to_string(), args[2].to_string());
+        r.create_timer(period, move |r| {
+            if let Err(e) = r.call_replicated("set", &[key.as_str(), value.as_str()]) {
+                r.log(LogLevel::Warning, &format!("Could not set {}: {}", key, e));
+            }
+        });
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write"
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.SLEEP milliseconds
+//
+// Blocks the client while a thread sleeps for the given time, replying once
+// it's done or with a nil after a second.
+struct SleepCommand;
+
+impl Command for SleepCommand {
+    fn name(&self) -> &'static str {
+        "hello.sleep"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let millis = args[1].parse::<u64>()?;
+        let client = r.block_client(time::Duration::seconds(1));
+        thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(millis));
+            let _ = client.unblock(|r| r.reply_string("slept"));
+        });
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.BGSET key value
+//
+// Sets a string key from a background thread, replying once it's set.
+struct BgsetCommand;
+
+impl Command for BgsetCommand {
+    fn name(&self) -> &'static str {
+        "hello.bgset"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let (key, value) = (args[1].to_string(), args[2].to_string());
+        let client = r.block_client(time::Duration::zero());
+        thread::spawn(move || {
+            let ctx = ThreadSafeContext::new();
+            let result = ctx.lock().call("set", &[key.as_str(), value.as_str()]).map(|_| ());
+            let _ = client.unblock(move |r| {
+                result?;
+                r.reply_ok();
+                Ok(())
+            });
+        });
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.TALLY key
+//
+// Increments the counter of the custom type held by `key`, replying with
+// the new count.
+struct TallyCommand;
+
+impl Command for TallyCommand {
+    fn name(&self) -> &'static str {
+        "hello.tally"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let tally_type = TypeRegistry::global().get::<Tally>(TALLY_TYPE)?;
+        let mut key = r.open_key_writable(args[1]);
+        let count = match key.get_module_value_mut(tally_type)? {
+            Some(tally) => {
+                tally.0 += 1;
+                tally.0
+            }
+            None => {
+                key.set_module_value(tally_type, Tally(1))?;
+                1
+            }
+        };
+        r.replicate_verbatim();
+        r.reply_integer(count as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.FAIL REPLY|RETURN code message
+//
+// Fails with `code message`, either by replying the error directly or by
+// returning it from the command.
+struct FailCommand;
+
+impl Command for FailCommand {
+    fn name(&self) -> &'static str {
+        "hello.fail"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        match args[1].to_lowercase().as_str() {
+            "reply" => r.reply_error(args[2], args[3]),
+            "return" => Err(RModError::with_code(args[2], args[3])),
+            _ => Err(error!("Unknown mode: {}", args[1])),
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.SAME key1 key2
+//
+// Replies with 1 if both arguments name the same key, and 0 otherwise.
+struct SameCommand;
+
+impl Command for SameCommand {
+    fn name(&self) -> &'static str {
+        "hello.same"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let first = r.open_key(args[1]);
+        let second = r.open_key(args[2]);
+        r.reply_integer(first.same_key_as(&second) as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 2, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.EXISTS key
+//
+// Replies with 1 if `key` exists, and 0 otherwise.
+struct ExistsCommand;
+
+impl Command for ExistsCommand {
+    fn name(&self) -> &'static str {
+        "hello.exists"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(r.open_key(args[1]).exists() as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.PTTL key
+//
+// Replies with the remaining time to live of `key` in milliseconds, or -1
+// if it has no expiry.
+struct PttlCommand;
+
+impl Command for PttlCommand {
+    fn name(&self) -> &'static str {
+        "hello.pttl"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        match key.get_expire()? {
+            Some(ttl) => r.reply_integer(ttl.num_milliseconds()),
+            None => r.reply_integer(-1),
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.PERSIST key
+//
+// Clears the expiry of `key`, replying like `PERSIST` with 1 if it had one,
+// and 0 otherwise.
+struct PersistCommand;
+
+impl Command for PersistCommand {
+    fn name(&self) -> &'static str {
+        "hello.persist"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        if key.get_expire()?.is_none() {
+            return r.reply_integer(0);
+        }
+        key.persist()?;
+        r.replicate_verbatim();
+        r.reply_integer(1)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.ZADD key score member
+//
+// Adds `member` to the sorted set at `key` with `score`, or updates its
+// score, through the module API.
+struct ZaddCommand;
+
+impl Command for ZaddCommand {
+    fn name(&self) -> &'static str {
+        "hello.zadd"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let score = args[2].parse::<f64>()?;
+        let key = r.open_key_writable(args[1]);
+        key.zadd(score, args[3])?;
+        r.replicate_verbatim();
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.ZSCORE key member
+//
+// Replies with the score of `member` in the sorted set at `key`, or nil if
+// it's not a member.
+struct ZscoreCommand;
+
+impl Command for ZscoreCommand {
+    fn name(&self) -> &'static str {
+        "hello.zscore"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        match key.zscore(args[2])? {
+            Some(score) => r.reply_double(score),
+            None => {
+                r.reply_null();
+                Ok(())
+            }
+        }
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.ZRANGE key start stop
+//
+// Replies with the members ranked from `start` to `stop` in the sorted set
+// at `key`, each followed by its score, like `ZRANGE ... WITHSCORES`.
+struct ZrangeCommand;
+
+impl Command for ZrangeCommand {
+    fn name(&self) -> &'static str {
+        "hello.zrange"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let start = args[2].parse::<i64>()?;
+        let stop = args[3].parse::<i64>()?;
+        let members = r.open_key_writable(args[1]).zrange(start, stop)?;
+        r.reply_array(members.len() as i64 * 2)?;
+        for (member, score) in members {
+            r.reply_string(&member)?;
+            r.reply_double(score)?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        4
+    }
+}
+
+// HELLO.SUMKEYS numkeys key [key ...]
+//
+// Replies with the sum of the integers held by the given keys, treating
+// missing keys as 0. The number of keys comes first, so the keys are
+// described by a key spec rather than a range of arguments.
+struct SumkeysCommand;
+
+impl Command for SumkeysCommand {
+    fn name(&self) -> &'static str {
+        "hello.sumkeys"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let numkeys = args[1].parse::<usize>()?;
+        if numkeys == 0 || args.len() != numkeys + 2 {
+            return Err(error!("Expected {} keys", numkeys));
+        }
+
+        let mut sum: i64 = 0;
+        for key in &args[2..] {
+            if let Some(value) = r.open_key(key).read()? {
+                sum += value.parse::<i64>()?;
+            }
+        }
+        r.reply_integer(sum)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_specs(&self) -> Option<KeySpecBuilder> {
+        Some(KeySpecBuilder::new().key_spec(
+            raw::KeySpecFlags::RO | raw::KeySpecFlags::ACCESS,
+            BeginSearch::Index(1),
+            FindKeys::Keynum { keynumidx: 0, firstkey: 1, keystep: 1 },
+        ))
+    }
+
+    fn arity(&self) -> i32 {
+        -3
+    }
+}
+
+// HELLO.SADD key member
+//
+// Adds `member` to the set at `key`, replying with 1 if it wasn't a member
+// yet, and 0 otherwise.
+struct SaddCommand;
+
+impl Command for SaddCommand {
+    fn name(&self) -> &'static str {
+        "hello.sadd"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(r.sadd(args[1], args[2])? as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.SISMEMBER key member
+//
+// Replies with 1 if `member` belongs to the set at `key`, and 0 otherwise.
+struct SismemberCommand;
+
+impl Command for SismemberCommand {
+    fn name(&self) -> &'static str {
+        "hello.sismember"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(r.sismember(args[1], args[2])? as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.DEL key [UNLINK]
+//
+// Deletes `key` like `DEL`, or like `UNLINK` if asked to, replying with 1
+// if it existed, and 0 otherwise.
+struct DelCommand;
+
+impl Command for DelCommand {
+    fn name(&self) -> &'static str {
+        "hello.del"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let unlink = match args.get(2) {
+            None => false,
+            Some(arg) if arg.eq_ignore_ascii_case("unlink") => true,
+            Some(arg) => return Err(error!("Unknown option: {}", arg)),
+        };
+
+        let key = r.open_key_writable(args[1]);
+        if key.key_type() == KeyType::Empty {
+            return r.reply_integer(0);
+        }
+        if unlink {
+            key.unlink()?;
+        } else {
+            key.delete()?;
+        }
+        r.replicate_verbatim();
+        r.reply_integer(1)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.MSET key value [key value ...]
+//
+// Sets every key to its value like `MSET`, as long as the keys all hash to
+// the same cluster slot.
+struct MsetCommand;
+
+impl Command for MsetCommand {
+    fn name(&self) -> &'static str {
+        "hello.mset"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        if args.len() % 2 == 0 {
+            return Err(error!("Expected key and value pairs"));
+        }
+
+        let keys: Vec<&str> = args[1..].iter().step_by(2).cloned().collect();
+        r.assert_same_slot(&keys)?;
+        r.call_replicated("mset", &args[1..])?;
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, -1, 2)
+    }
+
+    fn arity(&self) -> i32 {
+        -3
+    }
+}
+
+// HELLO.CARD key
+//
+// Replies with the number of elements held by `key`, whatever its type, or
+// the length of a string.
+struct CardCommand;
+
+impl Command for CardCommand {
+    fn name(&self) -> &'static str {
+        "hello.card"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let key = r.open_key_writable(args[1]);
+        r.reply_integer(key.cardinality()? as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        2
+    }
+}
+
+// HELLO.CALL command [arg ...]
+//
+// Calls `command` with the given arguments and replies with its reply.
+struct CallCommand;
+
+impl Command for CallCommand {
+    fn name(&self) -> &'static str {
+        "hello.call"
+    }
+
+    fn run_reply(&self, r: Redis, args: &[&str]) -> Result<Reply, RModError> {
+        r.call(args[1], &args[2..])?.to_reply()
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.STRLENS key [key ...]
+//
+// Replies with the name and length of every given key holding a non-empty
+// string, calling `STRLEN` on each key in between the replied elements.
+struct StrlensCommand;
+
+impl Command for StrlensCommand {
+    fn name(&self) -> &'static str {
+        "hello.strlens"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let mut array = r.reply_array_deferred()?;
+        for key in &args[1..] {
+            let len = array.call("strlen", &[key])?.to_integer()?;
+            if len > 0 {
+                array.reply_element(|r| r.reply_string(key))?;
+                array.reply_element(|r| r.reply_integer(len))?;
+            }
+        }
+        array.finish();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, -1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.UNIQUE element [element ...]
+//
+// Replies with a set of the distinct elements given, in the order they first
+// appear.
+struct UniqueCommand;
+
+impl Command for UniqueCommand {
+    fn name(&self) -> &'static str {
+        "hello.unique"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let mut unique: Vec<&str> = Vec::new();
+        for element in &args[1..] {
+            if !unique.contains(element) {
+                unique.push(element);
+            }
+        }
+
+        r.reply_set(unique.len() as i64)?;
+        for element in unique {
+            r.reply_string(element)?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.APPLIED
+//
+// Replies with the number of times the `hello.limit` config was applied.
+struct AppliedCommand;
+
+impl Command for AppliedCommand {
+    fn name(&self) -> &'static str {
+        "hello.applied"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(LIMIT_APPLIED.load(Ordering::Relaxed))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.PROXY command [arg ...]
+//
+// Calls `command` with the given arguments and passes its reply on as is.
+struct ProxyCommand;
+
+impl Command for ProxyCommand {
+    fn name(&self) -> &'static str {
+        "hello.proxy"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let reply = r.call(args[1], &args[2..])?;
+        r.reply_with_call_reply(&reply)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom"
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.SUM number [number ...]
+//
+// Replies with the sum of the given integers, which may not fit in 64 bits.
+struct SumCommand;
+
+impl Command for SumCommand {
+    fn name(&self) -> &'static str {
+        "hello.sum"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let mut sum: i128 = 0;
+        for arg in &args[1..] {
+            sum = sum
+                .checked_add(arg.parse::<i128>()?)
+                .ok_or_else(|| RModError::with_code("ERR", "sum would overflow"))?;
+        }
+        r.reply_i128(sum)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.ZHALF key member
+//
+// Replies with half of the score of `member` in the sorted set at `key`, as
+// read through `ZSCORE`.
+struct ZhalfCommand;
+
+impl Command for ZhalfCommand {
+    fn name(&self) -> &'static str {
+        "hello.zhalf"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let score = r.call("zscore", &[args[1], args[2]])?.to_double()?;
+        r.reply_double(score / 2.0)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.LOG level message
+//
+// Writes `message` to the server log at `level`, e.g. `warning`.
+struct LogCommand;
+
+impl Command for LogCommand {
+    fn name(&self) -> &'static str {
+        "hello.log"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let level = match args[1].to_lowercase().as_str() {
+            "debug" => LogLevel::Debug,
+            "verbose" => LogLevel::Verbose,
+            "notice" => LogLevel::Notice,
+            "warning" => LogLevel::Warning,
+            _ => return Err(error!("Unknown log level: {}", args[1])),
+        };
+        r.log(level, args[2]);
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.SPLIT message separator
+//
+// Replies with the parts of the message between separators, replying each
+// part directly and setting the length of the array once they're all sent.
+struct SplitCommand;
+
+impl Command for SplitCommand {
+    fn name(&self) -> &'static str {
+        "hello.split"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let array = r.reply_array_deferred()?;
+        let mut len = 0;
+        let res = args[1].split(args[2]).try_for_each(|part| {
+            r.reply_string(part)?;
+            len += 1;
+            Ok(())
+        });
+        // Also on errors, so that the parts already sent make a valid reply.
+        array.set_len(len);
+        res
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.BGINCR key [PANIC]
+//
+// Queues a job incrementing `key` on the worker pool, optionally panicking
+// once it's done, and replies as soon as it's queued.
+struct BgincrCommand;
+
+impl Command for BgincrCommand {
+    fn name(&self) -> &'static str {
+        "hello.bgincr"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let panics = match args.get(2) {
+            None => false,
+            Some(arg) if arg.eq_ignore_ascii_case("panic") => true,
+            Some(arg) => return Err(error!("Unknown option: {}", arg)),
+        };
+        let pool = POOL.get().ok_or_else(|| error!("No worker pool"))?;
+
+        let key = args[1].to_string();
+        pool.submit(move |r| {
+            if let Err(e) = r.call_replicated("incr", &[&key]) {
+                r.log(LogLevel::Warning, &format!("Could not increment {}: {}", key, e));
+            }
+            if panics {
+                panic!("asked to panic");
+            }
+        })?;
+        r.reply_ok();
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "write deny-oom fast"
+    }
+
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (1, 1, 1)
+    }
+
+    fn arity(&self) -> i32 {
+        -2
+    }
+}
+
+// HELLO.PAIRS field value [field value ...]
+//
+// Replies with a map of the fields given to their values.
+struct PairsCommand;
+
+impl Command for PairsCommand {
+    fn name(&self) -> &'static str {
+        "hello.pairs"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let pairs = &args[1..];
+        if pairs.len() % 2 != 0 {
+            return Err(error!("Every field needs a value"));
+        }
+
+        r.reply_map((pairs.len() / 2) as i64)?;
+        for pair in pairs.chunks(2) {
+            r.reply_string(pair[0])?;
+            r.reply_string(pair[1])?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        -3
+    }
+}
+
+// HELLO.ARGS
+//
+// Replies with the arguments the module was loaded with.
+struct ArgsCommand;
+
+impl Command for ArgsCommand {
+    fn name(&self) -> &'static str {
+        "hello.args"
+    }
+
+    fn run(&self, r: Redis, _args: &[&str]) -> Result<(), RModError> {
+        let args = ARGS.get().map(|args| args.as_slice()).unwrap_or(&[]);
+        r.reply_array(args.len() as i64)?;
+        for arg in args {
+            r.reply_string(arg)?;
+        }
+        Ok(())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.COUNT [arg ...]
+//
+// Replies with the number of arguments it was given.
+struct CountCommand;
+
+impl Command for CountCommand {
+    fn name(&self) -> &'static str {
+        "hello.count"
+    }
+
+    fn run_reply(&self, _r: Redis, args: &[&str]) -> Result<Reply, RModError> {
+        Ok(Reply::Integer(args.len() as i64 - 1))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+}
+
+// HELLO.TYPES
+//
+// Replies with one value of each kind of reply.
+struct TypesCommand;
+
+impl Command for TypesCommand {
+    fn name(&self) -> &'static str {
+        "hello.types"
+    }
+
+    fn run_reply(&self, _r: Redis, _args: &[&str]) -> Result<Reply, RModError> {
+        Ok(Reply::Array(vec![
+            Reply::Integer(1),
+            Reply::String("two".to_string()),
+            Reply::Double(3.5),
+            Reply::Bool(true),
+            Reply::Nil,
+            Reply::Map(vec![(
+                Reply::String("key".to_string()),
+                Reply::Array(vec![Reply::Integer(4)]),
+            )]),
+        ]))
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        1
+    }
+}
+
+// HELLO.STR <subcommand> ...
+//
+// Groups the string subcommands below.
+struct StrCommand;
+
+impl Command for StrCommand {
+    fn name(&self) -> &'static str {
+        "hello.str"
+    }
+
+    fn str_flags(&self) -> &'static str {
+        ""
+    }
+}
+
+// HELLO.STR UPPER message
+//
+// Replies with the message in upper case.
+struct StrUpperCommand;
+
+impl Command for StrUpperCommand {
+    fn name(&self) -> &'static str {
+        "upper"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_string(&args[2].to_uppercase())
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+// HELLO.STR LEN message
+//
+// Replies with the length of the message in bytes.
+struct StrLenCommand;
+
+impl Command for StrLenCommand {
+    fn name(&self) -> &'static str {
+        "len"
+    }
+
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        r.reply_integer(args[2].len() as i64)
+    }
+
+    fn str_flags(&self) -> &'static str {
+        "readonly fast"
+    }
+
+    fn arity(&self) -> i32 {
+        3
+    }
+}
+
+rmod_load!(
+    (Echo_Cmd, EchoCommand),
+    (Incr_Cmd, IncrCommand),
+    (Get_Cmd, GetCommand),
+    (Client_Cmd, ClientCommand),
+    (Words_Cmd, WordsCommand),
+    (Readme_Cmd, ReadmeCommand),
+    (Big_Cmd, BigCommand),
+    (Keys_Cmd, KeysCommand),
+    (Hfields_Cmd, HfieldsCommand),
+    (Getrange_Cmd, GetrangeCommand),
+    (Setrange_Cmd, SetrangeCommand),
+    (Lrange_Cmd, LrangeCommand),
+    (Lindex_Cmd, LindexCommand),
+    (Lset_Cmd, LsetCommand),
+    (Linsert_Cmd, LinsertCommand),
+    (Reload_Cmd, ReloadCommand),
+    (Strlen_Cmd, StrlenCommand),
+    (Hex_Cmd, HexCommand),
+    (Inspect_Cmd, InspectCommand),
+    (Cluster_Cmd, ClusterCommand),
+    (Broadcast_Cmd, BroadcastCommand),
+    (Hset_Cmd, HsetCommand),
+    (Incrby_Cmd, IncrbyCommand),
+    (Getset_Cmd, GetsetCommand),
+    (Time_Cmd, TimeCommand),
+    (Random_Cmd, RandomCommand),
+    (DbSize_Cmd, DbSizeCommand),
+    (Xadd_Cmd, XaddCommand),
+    (Xrange_Cmd, XrangeCommand),
+    (Peek_Cmd, PeekCommand),
+    (Idle_Cmd, IdleCommand),
+    (Halfway_Cmd, HalfwayCommand),
+    (Partial_Cmd, PartialCommand),
+    (Rename_Cmd, RenameCommand),
+    (Setin_Cmd, SetinCommand),
+    (Truncate_Cmd, TruncateCommand),
+    (Mark_Cmd, MarkCommand),
+    (Flags_Cmd, FlagsCommand),
+    (Limit_Cmd, LimitCommand),
+    (Type_Cmd, TypeCommand),
+    (Hgetall_Cmd, HgetallCommand),
+    (Hdel_Cmd, HdelCommand),
+    (Later_Cmd, LaterCommand),
+    (Sleep_Cmd, SleepCommand),
+    (Bgset_Cmd, BgsetCommand),
+    (Tally_Cmd, TallyCommand),
+    (Fail_Cmd, FailCommand),
+    (Same_Cmd, SameCommand),
+    (Exists_Cmd, ExistsCommand),
+    (Pttl_Cmd, PttlCommand),
+    (Persist_Cmd, PersistCommand),
+    (Zadd_Cmd, ZaddCommand),
+    (Zscore_Cmd, ZscoreCommand),
+    (Zrange_Cmd, ZrangeCommand),
+    (Sumkeys_Cmd, SumkeysCommand),
+    (Sadd_Cmd, SaddCommand),
+    (Sismember_Cmd, SismemberCommand),
+    (Del_Cmd, DelCommand),
+    (Mset_Cmd, MsetCommand),
+    (Card_Cmd, CardCommand),
+    (Call_Cmd, CallCommand),
+    (Strlens_Cmd, StrlensCommand),
+    (Unique_Cmd, UniqueCommand),
+    (Applied_Cmd, AppliedCommand),
+    (Proxy_Cmd, ProxyCommand),
+    (Sum_Cmd, SumCommand),
+    (Zhalf_Cmd, ZhalfCommand),
+    (Log_Cmd, LogCommand),
+    (Split_Cmd, SplitCommand),
+    (Bgincr_Cmd, BgincrCommand),
+    (Pairs_Cmd, PairsCommand),
+    (Args_Cmd, ArgsCommand),
+    (Count_Cmd, CountCommand),
+    (Types_Cmd, TypesCommand),
+    (Str_Cmd, StrCommand, [(StrUpper_Cmd, StrUpperCommand), (StrLen_Cmd, StrLenCommand)]);
+    init = init
+);
@@ -7,12 +7,42 @@ pub enum RModError {
     Generic(GenericError),
     FromUtf8(std::string::FromUtf8Error),
     ParseInt(std::num::ParseIntError),
+    ParseFloat(std::num::ParseFloatError),
+    Utf8(std::str::Utf8Error),
+    Nul(std::ffi::NulError),
 }
 
 impl RModError {
     pub fn generic(message: &str) -> RModError {
         RModError::Generic(GenericError::new(message))
     }
+
+    /// Creates an error that's replied to the client prefixed with a Redis
+    /// error code like `WRONGTYPE` instead of the default `ERR`.
+    pub fn with_code(code: &str, message: &str) -> RModError {
+        RModError::Generic(GenericError::with_code(code, message))
+    }
+
+    /// Returns the error wrapped by the variant, which displays the same as
+    /// the `RModError` itself.
+    pub(crate) fn inner(&self) -> &(dyn error::Error + 'static) {
+        match *self {
+            RModError::Generic(ref err) => err,
+            RModError::FromUtf8(ref err) => err,
+            RModError::ParseInt(ref err) => err,
+            RModError::ParseFloat(ref err) => err,
+            RModError::Utf8(ref err) => err,
+            RModError::Nul(ref err) => err,
+        }
+    }
+
+    /// Returns the Redis error code of the error, if it carries one.
+    pub fn code(&self) -> Option<&str> {
+        match *self {
+            RModError::Generic(ref err) => err.code(),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::string::FromUtf8Error> for RModError {
@@ -27,67 +57,118 @@ impl From<std::num::ParseIntError> for RModError {
     }
 }
 
+impl From<std::num::ParseFloatError> for RModError {
+    fn from(err: std::num::ParseFloatError) -> RModError {
+        RModError::ParseFloat(err)
+    }
+}
+
+impl From<std::str::Utf8Error> for RModError {
+    fn from(err: std::str::Utf8Error) -> RModError {
+        RModError::Utf8(err)
+    }
+}
+
+impl From<std::ffi::NulError> for RModError {
+    fn from(err: std::ffi::NulError) -> RModError {
+        RModError::Nul(err)
+    }
+}
+
 impl fmt::Display for RModError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            // Both underlying errors already impl `Display`, so we defer to
+            // All underlying errors already impl `Display`, so we defer to
             // their implementations.
             RModError::Generic(ref err) => write!(f, "{}", err),
             RModError::FromUtf8(ref err) => write!(f, "{}", err),
             RModError::ParseInt(ref err) => write!(f, "{}", err),
+            RModError::ParseFloat(ref err) => write!(f, "{}", err),
+            RModError::Utf8(ref err) => write!(f, "{}", err),
+            RModError::Nul(ref err) => write!(f, "{}", err),
         }
     }
 }
 
 impl error::Error for RModError {
-    fn description(&self) -> &str {
-        // Both underlying errors already impl `Error`, so we defer to their
-        // implementations.
-        match *self {
-            RModError::Generic(ref err) => err.description(),
-            RModError::FromUtf8(ref err) => err.description(),
-            RModError::ParseInt(ref err) => err.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&dyn error::Error> {
-        match *self {
-            // N.B. Both of these implicitly cast `err` from their concrete
-            // types (either `&io::Error` or `&num::ParseIntError`)
-            // to a trait object `&Error`. This works because both error types
-            // implement `Error`.
-            RModError::Generic(ref err) => Some(err),
-            RModError::FromUtf8(ref err) => Some(err),
-            RModError::ParseInt(ref err) => Some(err),
-        }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.inner())
     }
 }
 
 #[derive(Debug)]
 pub struct GenericError {
     message: String,
+    code:    Option<String>,
 }
 
 impl GenericError {
     pub fn new(message: &str) -> GenericError {
         GenericError {
             message: String::from(message),
+            code:    None,
         }
     }
+
+    pub fn with_code(code: &str, message: &str) -> GenericError {
+        GenericError {
+            message: String::from(message),
+            code:    Some(String::from(code)),
+        }
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
 }
 
-impl<'a> fmt::Display for GenericError {
+impl fmt::Display for GenericError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Store error: {}", self.message)
+        match self.code {
+            // Coded errors display exactly as Redis sends them to clients.
+            Some(ref code) => write!(f, "{} {}", code, self.message),
+            None => write!(f, "Store error: {}", self.message),
+        }
     }
 }
 
-impl<'a> error::Error for GenericError {
-    fn description(&self) -> &str {
-        self.message.as_str()
+impl error::Error for GenericError {}
+
+#[cfg(test)]
+mod tests {
+    use super::RModError;
+    use std::error::Error;
+
+    #[test]
+    fn source_is_the_wrapped_error() {
+        let e = RModError::from("x".parse::<i64>().unwrap_err());
+        let source = e.source().expect("no source");
+        assert_eq!(source.to_string(), "invalid digit found in string");
+        assert!(source.downcast_ref::<std::num::ParseIntError>().is_some());
+
+        let e = RModError::generic("no such key");
+        assert_eq!(e.source().expect("no source").to_string(), "Store error: no such key");
+    }
+
+    // Parses a score sent by a client, like a command would.
+    fn parse_score(arg: &[u8]) -> Result<f64, RModError> {
+        Ok(std::str::from_utf8(arg)?.parse::<f64>()?)
     }
 
-    fn cause(&self) -> Option<&dyn error::Error> {
-        None
+    #[test]
+    fn float_and_utf8_errors_propagate_with_question_mark() {
+        assert!(matches!(parse_score(b"1.5"), Ok(score) if score == 1.5));
+
+        let e = parse_score(b"x").unwrap_err();
+        assert!(matches!(e, RModError::ParseFloat(_)));
+        assert_eq!(e.to_string(), "invalid float literal");
+
+        let e = parse_score(&[b'1', 0xff, 0xfe]).unwrap_err();
+        assert!(matches!(e, RModError::Utf8(_)));
+        assert!(e.source().unwrap().downcast_ref::<std::str::Utf8Error>().is_some());
     }
 }
@@ -13,6 +13,13 @@ pub use crate::error::RModError;
 
 use libc::c_int;
 
+/// The init function `rmod_load!` uses when none is given, which ignores the
+/// module's arguments.
+#[doc(hidden)]
+pub fn no_init(_initializer: &RedisModuleInitializer, _args: &[&str]) -> Result<(), RModError> {
+    Ok(())
+}
+
 pub struct RedisModuleInitializer {
     ctx: *mut raw::RedisModuleCtx,
     module_name: &'static str,
@@ -48,4 +55,91 @@ impl RedisModuleInitializer {
         return raw::Status::Ok;
 
     }
+
+    /// Runs a module's init function with the arguments given to
+    /// `loadmodule` after the module name, and maps its result to the status
+    /// expected back from `RedisModule_OnLoad`. See `rmod_load!`.
+    ///
+    /// An error is logged, and makes Redis refuse to load the module.
+    pub fn run_init(
+        &self,
+        argv: *mut *mut raw::RedisModuleString,
+        argc: c_int,
+        init: fn(&RedisModuleInitializer, &[&str]) -> Result<(), RModError>
+    ) -> raw::Status {
+        let result = redis::parse_args(argv, argc)
+            .map_err(RModError::from)
+            .and_then(|args| {
+                let str_args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                init(self, str_args.as_slice())
+            });
+        match result {
+            Ok(()) => raw::Status::Ok,
+            Err(e) => {
+                let r = unsafe { redis::Redis::from_ctx(self.ctx) };
+                r.log(redis::LogLevel::Warning, format!("Could not initialize module: {}", e).as_str());
+                raw::Status::Err
+            }
+        }
+    }
+
+    /// Creates a custom type and adds it to the module's `TypeRegistry`,
+    /// where commands can look it up by name. Call it after `run`.
+    pub fn register_type<T: redis::DataType + 'static>(
+        &self,
+        builder: redis::ModuleTypeBuilder<T>
+    ) -> Result<(), RModError> {
+        redis::TypeRegistry::global().register(self.ctx, builder)
+    }
+
+    /// Registers the module's configs and loads their values. Call it after
+    /// `run`. See `ConfigBuilder`.
+    pub fn load_configs(&self, builder: redis::ConfigBuilder) -> Result<(), RModError> {
+        builder.load(self.ctx)
+    }
+
+    /// Returns the `proto-max-bulk-len` the server was started with. See
+    /// `Redis::proto_max_bulk_len`.
+    pub fn proto_max_bulk_len(&self) -> Result<usize, RModError> {
+        unsafe { redis::Redis::from_ctx(self.ctx) }.proto_max_bulk_len()
+    }
+
+    /// Creates a pool of `size` worker threads with room for `capacity`
+    /// queued jobs. See `WorkerPool`.
+    pub fn worker_pool(&self, size: usize, capacity: usize) -> Result<redis::WorkerPool, RModError> {
+        redis::WorkerPool::new(self.ctx, size, capacity)
+    }
+
+    /// Registers the function that adds the module's sections to `INFO`.
+    /// See `Redis::register_info_func`.
+    pub fn register_info_func(&self, info_func: redis::InfoFunc) -> Result<(), RModError> {
+        unsafe { redis::Redis::from_ctx(self.ctx) }.register_info_func(info_func)
+    }
+
+    /// Registers the function receiving the cluster messages of `msg_type`.
+    /// See `Redis::register_cluster_message_receiver`.
+    pub fn register_cluster_message_receiver(&self, msg_type: u8, receiver: redis::ClusterMessageReceiver) {
+        unsafe { redis::Redis::from_ctx(self.ctx) }.register_cluster_message_receiver(msg_type, receiver)
+    }
+
+    /// Registers the filter that every command goes through before it runs.
+    /// See `Redis::register_command_filter`.
+    pub fn register_command_filter<F>(&self, filter: F, flags: raw::CommandFilterFlags) -> Result<(), RModError>
+    where
+        F: redis::CommandFilter + 'static,
+    {
+        unsafe { redis::Redis::from_ctx(self.ctx) }.register_command_filter(filter, flags)
+    }
+
+    /// Runs a module's unload function and maps its result to the status
+    /// expected back from `RedisModule_OnUnload`. See `rmod_unload!`.
+    pub fn on_unload(
+        ctx: *mut raw::RedisModuleCtx,
+        unload: fn(*mut raw::RedisModuleCtx) -> Result<(), RModError>
+    ) -> raw::Status {
+        match unload(ctx) {
+            Ok(()) => raw::Status::Ok,
+            Err(_) => raw::Status::Err,
+        }
+    }
 }
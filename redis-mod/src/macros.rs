@@ -8,18 +8,47 @@ macro_rules! error {
     }
 }
 
-//macro_rules! log_debug {
-//    ($logger:expr, $target:expr) => {
-//        if cfg!(debug_assertions) {
-//            $logger.log_debug($target)
-//        }
-//    };
-//    ($logger:expr, $target:expr, $($arg:tt)*) => {
-//        if cfg!(debug_assertions) {
-//            $logger.log_debug(format!($target, $($arg)+).as_str())
-//        }
-//    }
-//}
+/// Logs a formatted message through `Redis::log` at the given `LogLevel`,
+/// e.g. `log!(r, Warning, "x={}", x)`. Nothing is evaluated past the level,
+/// including the format arguments, unless `LogLevel::is_enabled`, so debug
+/// messages cost nothing in release builds.
+#[macro_export]
+macro_rules! log {
+    ($redis:expr, $level:ident, $message:expr) => {
+        if $crate::redis::LogLevel::$level.is_enabled() {
+            $redis.log($crate::redis::LogLevel::$level, $message)
+        }
+    };
+    ($redis:expr, $level:ident, $message:expr, $($arg:tt)*) => {
+        if $crate::redis::LogLevel::$level.is_enabled() {
+            $redis.log($crate::redis::LogLevel::$level, format!($message, $($arg)+).as_str())
+        }
+    }
+}
+
+/// Same as `log!`, for crates that also use the `log` crate's `log!`.
+#[macro_export]
+macro_rules! rmod_log {
+    ($($arg:tt)+) => {
+        $crate::log!($($arg)+)
+    }
+}
+
+/// Logs a formatted message through `Redis::log_debug`. The call, including
+/// the formatting of its arguments, is compiled out of release builds.
+#[macro_export]
+macro_rules! log_debug {
+    ($redis:expr, $message:expr) => {
+        if cfg!(debug_assertions) {
+            $redis.log_debug($message)
+        }
+    };
+    ($redis:expr, $message:expr, $($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $redis.log_debug(format!($message, $($arg)+).as_str())
+        }
+    }
+}
 
 #[macro_export]
 macro_rules! bultin_command {
@@ -37,12 +66,59 @@ macro_rules! bultin_command {
     }
 }
 
+/// Emits the `RedisModule_OnUnload` symbol, forwarding to `$unload` through
+/// `RedisModuleInitializer::on_unload`.
+///
+/// `$unload` must be a `fn(*mut raw::RedisModuleCtx) -> Result<(), RModError>`.
+/// Redis calls it on `MODULE UNLOAD` while the module's commands are still
+/// registered, so it's the place to stop background threads and release
+/// global resources. Returning an error makes Redis refuse the unload and keep
+/// the module loaded. It isn't called when the server shuts down.
+#[macro_export]
+macro_rules! rmod_unload {
+    ($unload: path) => {
+        #[allow(non_snake_case)]
+        #[no_mangle]
+        pub extern "C" fn RedisModule_OnUnload(
+            ctx: *mut raw::RedisModuleCtx,
+        ) -> raw::Status {
+            RedisModuleInitializer::on_unload(ctx, $unload)
+        }
+    }
+}
+
+/// Emits `RedisModule_OnLoad`, which registers each `$command` under the
+/// symbol `$builtin`, along with `RedisModule_OnUnload` when `on_unload` is
+/// given.
+///
+/// A command can be followed by a list of subcommands, e.g.
+/// `(MyMod_Cmd, MyModCommand, [(Get_Cmd, GetCommand), (Set_Cmd, SetCommand)])`
+/// registers `mymod get` and `mymod set`. The parent then only groups its
+/// subcommands, and its own `run` is never called. Subcommands require
+/// Redis 7, and loading the module fails on older servers.
+///
+/// `init` is a `fn(&RedisModuleInitializer, &[&str]) -> Result<(), RModError>`
+/// that's given the arguments following the module path in `loadmodule`
+/// (e.g. `loadmodule ./mymod.so arg1 arg2`). It runs before the commands are
+/// registered, so it's the place to parse those arguments into the module's
+/// configuration, and to register types and configs. Returning an error makes
+/// Redis refuse to load the module.
 #[macro_export]
 macro_rules! rmod_load {
-    ( $( ($builtin: ident ,$command: ident)),*) => {
+    ( @handler $builtin: ident ) => {
+        Some($builtin)
+    };
+    ( @handler $builtin: ident $( $sub_builtin: ident )+ ) => {
+        None
+    };
+    ( @load $init: path;
+      $( ($builtin: ident, $command: ident $(, [ $( ($sub_builtin: ident, $sub_command: ident) ),* ] )? ) ),* ) => {
 
         $(
             bultin_command!($builtin, $command);
+            $( $(
+                bultin_command!($sub_builtin, $sub_command);
+            )* )?
         )*
 
         #[allow(non_snake_case)]
@@ -53,36 +129,159 @@ macro_rules! rmod_load {
             argv: *mut *mut raw::RedisModuleString,
             argc: c_int,
         ) -> raw::Status {
-            if RedisModuleInitializer::new(
+            let initializer = RedisModuleInitializer::new(
               ctx,
               MODULE_NAME,
               MODULE_VERSION
-            ).run() == raw::Status::Err
-            {
+            );
+            if initializer.run() == raw::Status::Err {
+                return raw::Status::Err;
+            }
+
+            if initializer.run_init(argv, argc, $init) == raw::Status::Err {
                 return raw::Status::Err;
             }
 
             $(
 
                 let command = $command {};
+                let (firstkey, lastkey, keystep) = command.key_spec();
                 if raw::create_command(
                     ctx,
                     format!("{}\0", command.name()).as_ptr(),
-                    Some($builtin),
+                    rmod_load!(@handler $builtin $( $( $sub_builtin )* )? ),
                     format!("{}\0", command.str_flags()).as_ptr(),
-                    0,
-                    0,
-                    0,
+                    firstkey,
+                    lastkey,
+                    keystep,
                  ) == raw::Status::Err
                  {
                      return raw::Status::Err;
                  }
 
+                 if Command::set_command_info(&command, ctx).is_err() {
+                     return raw::Status::Err;
+                 }
+
+                 $( $(
+                     let subcommand = $sub_command {};
+                     if Command::create_subcommand(&command, &subcommand, $sub_builtin, ctx).is_err() {
+                         return raw::Status::Err;
+                     }
+                 )* )?
+
             )*
 
             raw::Status::Ok
 
 
         }
+    };
+    ( $( $command: tt ),* ; init = $init: path, on_unload = $unload: path ) => {
+        rmod_load!(@load $init; $( $command ),* );
+        rmod_unload!($unload);
+    };
+    ( $( $command: tt ),* ; init = $init: path ) => {
+        rmod_load!(@load $init; $( $command ),* );
+    };
+    ( $( $command: tt ),* ; on_unload = $unload: path ) => {
+        rmod_load!(@load $crate::no_init; $( $command ),* );
+        rmod_unload!($unload);
+    };
+    ( $( $command: tt ),* ) => {
+        rmod_load!(@load $crate::no_init; $( $command ),* );
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redis::LogLevel;
+    use std::cell::{Cell, RefCell};
+
+    // Stands in for `Redis`, recording what the macros log.
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<(&'static str, String)>>);
+
+    impl Recorder {
+        fn log(&self, level: LogLevel, message: &str) {
+            self.0.borrow_mut().push((level.as_str(), message.to_string()));
+        }
+
+        fn log_debug(&self, message: &str) {
+            self.log(LogLevel::Notice, message);
+        }
+    }
+
+    #[test]
+    fn log_passes_each_level() {
+        let r = Recorder::default();
+        log!(r, Verbose, "x={}", 1);
+        log!(r, Notice, "{} and {}", "a", "b");
+        log!(r, Warning, "done");
+        rmod_log!(r, Warning, "x={}", 2);
+        assert_eq!(
+            r.0.into_inner(),
+            vec![
+                ("verbose", "x=1".to_string()),
+                ("notice", "a and b".to_string()),
+                ("warning", "done".to_string()),
+                ("warning", "x=2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_arguments_are_only_evaluated_in_debug_builds() {
+        let r = Recorder::default();
+        let evaluated = Cell::new(0);
+        let arg = || {
+            evaluated.set(evaluated.get() + 1);
+            "x"
+        };
+        log!(r, Debug, "{}", arg());
+        log_debug!(r, "{}", arg());
+
+        let expected = if cfg!(debug_assertions) {
+            vec![("debug", "x".to_string()), ("notice", "x".to_string())]
+        } else {
+            Vec::new()
+        };
+        assert_eq!(evaluated.get(), expected.len());
+        assert_eq!(r.0.into_inner(), expected);
+    }
+    // A module that can be told to refuse being unloaded.
+    mod unloadable {
+        use crate::error::RModError;
+        use crate::redis::raw;
+        use crate::RedisModuleInitializer;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        pub static REFUSE: AtomicBool = AtomicBool::new(false);
+
+        fn unload(_ctx: *mut raw::RedisModuleCtx) -> Result<(), RModError> {
+            if REFUSE.load(Ordering::SeqCst) {
+                return Err(error!("busy"));
+            }
+            Ok(())
+        }
+
+        rmod_unload!(unload);
+    }
+
+    #[test]
+    fn rmod_unload_emits_the_unload_symbol() {
+        use crate::redis::raw;
+        use std::ptr;
+        use std::sync::atomic::Ordering;
+
+        // Looked up by name, as Redis does when unloading the module.
+        #[allow(improper_ctypes)]
+        extern "C" {
+            fn RedisModule_OnUnload(ctx: *mut raw::RedisModuleCtx) -> raw::Status;
+        }
+
+        assert_eq!(unsafe { RedisModule_OnUnload(ptr::null_mut()) }, raw::Status::Ok);
+        unloadable::REFUSE.store(true, Ordering::SeqCst);
+        assert_eq!(unsafe { RedisModule_OnUnload(ptr::null_mut()) }, raw::Status::Err);
     }
 }
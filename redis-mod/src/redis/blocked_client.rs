@@ -0,0 +1,135 @@
+use crate::error::RModError;
+use crate::redis::{handle_run_result, raw, Redis, Reply};
+use libc::c_int;
+use std::os::raw::c_void;
+use time;
+
+type ReplyCallback = Box<dyn FnOnce(&Redis) -> Result<(), RModError> + Send>;
+
+/// `BlockedClient` is a client that's been blocked by a command while its
+/// reply is being worked out elsewhere, usually on a background thread.
+///
+/// It can be moved to another thread and is unblocked either by calling
+/// `unblock` or when the timeout given to `Redis::block_client` expires, in
+/// which case the client gets a nil reply. Dropping it without unblocking
+/// replies with an error so that the client isn't left hanging.
+pub struct BlockedClient {
+    pub(super) inner: *mut raw::RedisModuleBlockedClient,
+}
+
+// The module API allows a blocked client handle to be unblocked from any
+// thread.
+unsafe impl Send for BlockedClient {}
+
+impl BlockedClient {
+    /// Unblocks the client, which will then be replied to by running `reply`
+    /// on the main thread. `reply` works like `Command::run`, so an error it
+    /// returns is sent back to the client.
+    pub fn unblock<F>(mut self, reply: F) -> Result<(), RModError>
+    where
+        F: FnOnce(&Redis) -> Result<(), RModError> + Send + 'static,
+    {
+        let inner = self.inner;
+        self.inner = std::ptr::null_mut();
+        unblock_with(inner, Box::new(reply))
+    }
+}
+
+impl Drop for BlockedClient {
+    // Unblocks the client with an error if it hasn't been unblocked yet.
+    fn drop(&mut self) {
+        if !self.inner.is_null() {
+            let _ = unblock_with(
+                self.inner,
+                Box::new(|_: &Redis| Err(error!("Blocked client dropped without reply"))),
+            );
+        }
+    }
+}
+
+/// `ReplyChannel` carries the result of work done on another thread back to
+/// a blocked client, which is then replied to with it on the main thread.
+///
+/// Since a `Reply` only ever holds owned Rust data, it's safe to produce on
+/// any thread, unlike anything borrowed from Redis.
+pub struct ReplyChannel {
+    client: BlockedClient,
+}
+
+impl ReplyChannel {
+    pub fn new(client: BlockedClient) -> ReplyChannel {
+        ReplyChannel { client }
+    }
+
+    /// Unblocks the client and replies with `result`. An error is replied
+    /// the same way as one returned from `Command::run`.
+    pub fn send(self, result: Result<Reply, RModError>) -> Result<(), RModError> {
+        self.client.unblock(move |r: &Redis| r.reply(&result?))
+    }
+}
+
+impl Redis {
+    /// Blocks the client that invoked the current command until the returned
+    /// `BlockedClient` is unblocked or `timeout` expires. A zero timeout
+    /// blocks indefinitely.
+    ///
+    /// The command should return without replying after calling this.
+    pub fn block_client(&self, timeout: time::Duration) -> BlockedClient {
+        BlockedClient {
+            inner: raw::block_client(
+                self.ctx,
+                Some(reply_callback),
+                Some(timeout_callback),
+                Some(free_privdata),
+                timeout.num_milliseconds(),
+            ),
+        }
+    }
+}
+
+fn unblock_with(
+    bc: *mut raw::RedisModuleBlockedClient,
+    reply: ReplyCallback,
+) -> Result<(), RModError> {
+    let privdata: Box<Option<ReplyCallback>> = Box::new(Some(reply));
+    let privdata = Box::into_raw(privdata) as *mut c_void;
+    match raw::unblock_client(bc, privdata) {
+        raw::Status::Ok => Ok(()),
+        raw::Status::Err => {
+            drop(unsafe { Box::from_raw(privdata as *mut Option<ReplyCallback>) });
+            Err(error!("Error while unblocking client"))
+        }
+    }
+}
+
+extern "C" fn reply_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> raw::Status {
+    let privdata = raw::get_blocked_client_private_data(ctx) as *mut Option<ReplyCallback>;
+    let reply = unsafe { (*privdata).take() };
+    match reply {
+        Some(reply) => {
+            let r = Redis::new(ctx);
+            let result = reply(&r);
+            handle_run_result(ctx, result, r.replied.get())
+        }
+        None => raw::Status::Err,
+    }
+}
+
+extern "C" fn timeout_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> raw::Status {
+    raw::reply_with_null(ctx);
+    raw::Status::Ok
+}
+
+// Called once Redis is done with a client unblocked with private data,
+// whether it was replied to or disconnected in the meantime.
+extern "C" fn free_privdata(_ctx: *mut raw::RedisModuleCtx, privdata: *mut c_void) {
+    drop(unsafe { Box::from_raw(privdata as *mut Option<ReplyCallback>) });
+}
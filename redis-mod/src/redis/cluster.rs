@@ -0,0 +1,124 @@
+use crate::error::RModError;
+use crate::redis::{raw, Redis};
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use std::sync::Mutex;
+
+/// The function a module registers to receive the cluster messages of one
+/// type, given the id of the sending node, the message type and its payload.
+pub type ClusterMessageReceiver = fn(&Redis, &str, u8, &[u8]);
+
+// Redis passes no private data to receivers, so they're kept here for the
+// trampoline, indexed by message type.
+static RECEIVERS: Mutex<[Option<ClusterMessageReceiver>; 256]> = Mutex::new([None; 256]);
+
+impl Redis {
+    /// Returns the node id of this server in the cluster, or `None` when it
+    /// isn't running in cluster mode.
+    pub fn cluster_my_id(&self) -> Option<String> {
+        let id = raw::get_my_cluster_id();
+        if id.is_null() {
+            return None;
+        }
+        // Like the sender id given to receivers, the id is exactly
+        // `REDISMODULE_NODE_ID_LEN` bytes and isn't NUL-terminated.
+        let id = unsafe { slice::from_raw_parts(id as *const u8, raw::REDISMODULE_NODE_ID_LEN) };
+        Some(String::from_utf8_lossy(id).into_owned())
+    }
+
+    /// Returns the number of nodes in the cluster, including this one, or 0
+    /// when the server isn't running in cluster mode.
+    pub fn cluster_size(&self) -> usize {
+        raw::get_cluster_size()
+    }
+
+    /// Sends a message of `msg_type` to the node with the id `target`, or to
+    /// every other node of the cluster for `None`. The message is delivered
+    /// to the receiver the same module registered there for `msg_type`.
+    ///
+    /// Errors when the server isn't running in cluster mode, or when the
+    /// target isn't a known node. Delivery itself isn't acknowledged.
+    pub fn send_cluster_message(&self, target: Option<&str>, msg_type: u8, payload: &[u8]) -> Result<(), RModError> {
+        let target = match target {
+            Some(id) if id.len() != raw::REDISMODULE_NODE_ID_LEN => {
+                return Err(error!("Invalid cluster node id {:?}", id));
+            }
+            Some(id) => Some(CString::new(id)?),
+            None => None,
+        };
+        let len = match u32::try_from(payload.len()) {
+            Ok(len) => len,
+            Err(_) => return Err(error!("Cluster message payload is too large")),
+        };
+        let target_ptr = target.as_ref().map_or(ptr::null(), |id| id.as_ptr());
+        match raw::send_cluster_message(self.ctx, target_ptr, msg_type, payload.as_ptr(), len) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Could not send cluster message, not in cluster mode or unknown node")),
+        }
+    }
+
+    /// Registers `receiver` to be called with the messages of `msg_type`
+    /// that other nodes send through `send_cluster_message`, replacing any
+    /// receiver previously registered for it.
+    pub fn register_cluster_message_receiver(&self, msg_type: u8, receiver: ClusterMessageReceiver) {
+        RECEIVERS.lock().unwrap()[msg_type as usize] = Some(receiver);
+        raw::register_cluster_message_receiver(self.ctx, msg_type, Some(cluster_message_callback));
+    }
+}
+
+extern "C" fn cluster_message_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    sender_id: *const c_char,
+    msg_type: u8,
+    payload: *const u8,
+    len: u32,
+) {
+    // The lock is released before calling the receiver so that it can
+    // register receivers itself.
+    let receiver = RECEIVERS.lock().unwrap()[msg_type as usize];
+    if let Some(receiver) = receiver {
+        // The sender id is exactly `REDISMODULE_NODE_ID_LEN` bytes and isn't
+        // NUL-terminated.
+        let sender_id = unsafe { slice::from_raw_parts(sender_id as *const u8, raw::REDISMODULE_NODE_ID_LEN) };
+        let payload = if len == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(payload, len as usize) }
+        };
+        receiver(&Redis::new(ctx), &String::from_utf8_lossy(sender_id), msg_type, payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Receivers are plain functions, so what they're given is kept here.
+    static RECEIVED: Mutex<Vec<(String, u8, Vec<u8>)>> = Mutex::new(Vec::new());
+
+    fn record(_r: &Redis, sender_id: &str, msg_type: u8, payload: &[u8]) {
+        RECEIVED.lock().unwrap().push((sender_id.to_owned(), msg_type, payload.to_vec()));
+    }
+
+    #[test]
+    fn callback_passes_message_to_receiver() {
+        RECEIVERS.lock().unwrap()[7] = Some(record);
+
+        // The sender id isn't NUL-terminated, so it's followed by garbage.
+        let sender_id = format!("{}garbage", "a".repeat(raw::REDISMODULE_NODE_ID_LEN));
+        let sender_ptr = sender_id.as_ptr() as *const c_char;
+        cluster_message_callback(ptr::null_mut(), sender_ptr, 7, b"hello".as_ptr(), 5);
+        cluster_message_callback(ptr::null_mut(), sender_ptr, 7, ptr::null(), 0);
+        // Nothing is registered for this type.
+        cluster_message_callback(ptr::null_mut(), sender_ptr, 8, b"ignored".as_ptr(), 7);
+
+        let expected_id = "a".repeat(raw::REDISMODULE_NODE_ID_LEN);
+        assert_eq!(
+            *RECEIVED.lock().unwrap(),
+            vec![(expected_id.clone(), 7, b"hello".to_vec()), (expected_id, 7, Vec::new())]
+        );
+    }
+}
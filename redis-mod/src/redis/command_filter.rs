@@ -0,0 +1,108 @@
+use crate::error::RModError;
+use crate::redis::{manifest_redis_string, raw, Redis};
+use libc::c_int;
+use std::ptr;
+use std::sync::OnceLock;
+
+/// `CommandFilter` is implemented by a module to inspect and rewrite every
+/// command before it runs, including commands run from Lua scripts and (unless
+/// registered with `NOSELF`) the ones the module itself calls.
+///
+/// Filters run for every single command, so they should return quickly when
+/// a command isn't of interest to them.
+pub trait CommandFilter: Send + Sync {
+    fn filter(&self, ctx: &mut CommandFilterCtx);
+}
+
+// Redis passes no private data to the filter callback, so the filter is kept
+// here for the trampoline. A module only registers one.
+static COMMAND_FILTER: OnceLock<Box<dyn CommandFilter>> = OnceLock::new();
+
+/// `CommandFilterCtx` gives a `CommandFilter` access to the arguments of the
+/// command being filtered, the command name itself being the argument at
+/// position 0.
+pub struct CommandFilterCtx {
+    fctx: *mut raw::RedisModuleCommandFilterCtx,
+}
+
+impl CommandFilterCtx {
+    /// Returns the number of arguments, including the command name.
+    pub fn arg_count(&self) -> usize {
+        raw::command_filter_args_count(self.fctx).unwrap_or(0) as usize
+    }
+
+    /// Returns the argument at `pos`, or `None` if it's out of range.
+    pub fn arg_get(&self, pos: usize) -> Result<Option<String>, RModError> {
+        match raw::command_filter_arg_get(self.fctx, pos as c_int) {
+            Some(arg) if !arg.is_null() => Ok(Some(manifest_redis_string(arg)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Replaces the argument at `pos` with `arg`.
+    pub fn arg_replace(&mut self, pos: usize, arg: &str) -> Result<(), RModError> {
+        let arg = create_filter_arg(arg);
+        match raw::command_filter_arg_replace(self.fctx, pos as c_int, arg) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => {
+                raw::free_string(ptr::null_mut(), arg);
+                Err(error!("Could not replace argument {} of filtered command", pos))
+            }
+        }
+    }
+
+    /// Inserts `arg` so that it ends up at `pos`, shifting the arguments
+    /// after it.
+    pub fn arg_insert(&mut self, pos: usize, arg: &str) -> Result<(), RModError> {
+        let arg = create_filter_arg(arg);
+        match raw::command_filter_arg_insert(self.fctx, pos as c_int, arg) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => {
+                raw::free_string(ptr::null_mut(), arg);
+                Err(error!("Could not insert argument {} of filtered command", pos))
+            }
+        }
+    }
+
+    /// Deletes the argument at `pos`, shifting the arguments after it.
+    pub fn arg_delete(&mut self, pos: usize) -> Result<(), RModError> {
+        match raw::command_filter_arg_delete(self.fctx, pos as c_int) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => Err(error!("Could not delete argument {} of filtered command", pos)),
+        }
+    }
+}
+
+impl Redis {
+    /// Registers the filter that every command goes through before it runs.
+    /// Must be called from within `RedisModule_OnLoad`, and only once.
+    pub fn register_command_filter<F>(&self, filter: F, flags: raw::CommandFilterFlags) -> Result<(), RModError>
+    where
+        F: CommandFilter + 'static,
+    {
+        if COMMAND_FILTER.get().is_some() {
+            return Err(error!("A command filter is already registered"));
+        }
+        match raw::register_command_filter(self.ctx, command_filter_callback, flags) {
+            Some(registered) if !registered.is_null() => (),
+            Some(_) => return Err(error!("Could not register command filter")),
+            None => return Err(error!("Command filters require Redis 5.0.5 or later")),
+        }
+        // Only set once registered, so that a failed registration can be
+        // retried. Loading runs on the main thread, so there's no race.
+        let _ = COMMAND_FILTER.set(Box::new(filter));
+        Ok(())
+    }
+}
+
+// Arguments handed to Redis are owned by the filtered command from then on,
+// so they're created without a context to keep them out of auto memory.
+fn create_filter_arg(arg: &str) -> *mut raw::RedisModuleString {
+    raw::create_string(ptr::null_mut(), arg.as_ptr(), arg.len())
+}
+
+extern "C" fn command_filter_callback(fctx: *mut raw::RedisModuleCommandFilterCtx) {
+    if let Some(filter) = COMMAND_FILTER.get() {
+        filter.filter(&mut CommandFilterCtx { fctx });
+    }
+}
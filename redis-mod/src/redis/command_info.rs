@@ -0,0 +1,192 @@
+use crate::error::RModError;
+use crate::redis::raw;
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+
+/// `BeginSearch` is how Redis finds the first key of a key spec.
+pub enum BeginSearch {
+    /// The key is at a fixed argument index.
+    Index(i32),
+    /// The key follows `keyword`, which is searched for starting at the
+    /// argument index `startfrom` (or backwards from the end if negative).
+    Keyword { keyword: String, startfrom: i32 },
+}
+
+/// `FindKeys` is how Redis finds the rest of the keys of a key spec once the
+/// first one has been found.
+pub enum FindKeys {
+    /// Keys run up to `lastkey` (relative to the first key, or counted from
+    /// the end if negative) every `keystep` arguments.
+    Range { lastkey: i32, keystep: i32, limit: i32 },
+    /// The number of keys is given by the argument at `keynumidx`, with the
+    /// first key at `firstkey` and the others every `keystep` arguments.
+    Keynum { keynumidx: i32, firstkey: i32, keystep: i32 },
+}
+
+struct KeySpec {
+    flags:        raw::KeySpecFlags,
+    begin_search: BeginSearch,
+    find_keys:    FindKeys,
+}
+
+/// `KeySpecBuilder` describes where the keys of a command are in its
+/// arguments for commands that the legacy `(firstkey, lastkey, keystep)`
+/// triple can't describe, e.g. `GEORADIUS ... STORE dest`. Redis relies on
+/// key specs for cluster routing and ACL key permissions.
+///
+/// Return one from `Command::key_specs` to have it set when the command is
+/// registered. Key specs are only supported as of Redis 7, and are skipped
+/// on older servers.
+#[derive(Default)]
+pub struct KeySpecBuilder {
+    specs: Vec<KeySpec>,
+}
+
+impl KeySpecBuilder {
+    pub fn new() -> KeySpecBuilder {
+        KeySpecBuilder { specs: Vec::new() }
+    }
+
+    /// Adds a block of keys, with `flags` describing how the command uses
+    /// them (e.g. `RW | UPDATE`).
+    pub fn key_spec(
+        mut self,
+        flags: raw::KeySpecFlags,
+        begin_search: BeginSearch,
+        find_keys: FindKeys,
+    ) -> KeySpecBuilder {
+        self.specs.push(KeySpec {
+            flags,
+            begin_search,
+            find_keys,
+        });
+        self
+    }
+
+    /// Sets the key specs on the already created command `name`.
+    pub fn set(&self, ctx: *mut raw::RedisModuleCtx, name: &str) -> Result<(), RModError> {
+        set_command_info(ctx, name, 0, Some(self))
+    }
+}
+
+// Sets the arity (unless it's 0) and key specs of the already created command
+// `name` through `RedisModule_SetCommandInfo`, which is skipped on servers
+// before Redis 7.
+pub(super) fn set_command_info(
+    ctx: *mut raw::RedisModuleCtx,
+    name: &str,
+    arity: i32,
+    key_specs: Option<&KeySpecBuilder>,
+) -> Result<(), RModError> {
+    let name = CString::new(name)?;
+    let command = match raw::get_command(ctx, name.as_ptr()) {
+        Some(command) => command,
+        None => return Ok(()),
+    };
+    if command.is_null() {
+        return Err(error!("Error while setting command info, no such command"));
+    }
+
+    let specs: &[KeySpec] = key_specs.map_or(&[], |builder| builder.specs.as_slice());
+
+    // Keywords must outlive the call, after which Redis has copied them.
+    let keywords = specs
+        .iter()
+        .map(|spec| match spec.begin_search {
+            BeginSearch::Keyword { ref keyword, .. } => Ok(Some(CString::new(keyword.as_str())?)),
+            BeginSearch::Index(_) => Ok(None),
+        })
+        .collect::<Result<Vec<Option<CString>>, RModError>>()?;
+
+    let mut raw_key_specs: Vec<raw::RedisModuleCommandKeySpec> = specs
+        .iter()
+        .zip(keywords.iter())
+        .map(|(spec, keyword)| to_raw_key_spec(spec, keyword))
+        .collect();
+    // The array is terminated by a zeroed key spec.
+    raw_key_specs.push(to_raw_key_spec_terminator());
+
+    let version = command_info_version();
+    let info = raw::RedisModuleCommandInfo {
+        version:    &version,
+        summary:    ptr::null(),
+        complexity: ptr::null(),
+        since:      ptr::null(),
+        history:    ptr::null_mut(),
+        tips:       ptr::null(),
+        arity,
+        key_specs:  if specs.is_empty() { ptr::null_mut() } else { raw_key_specs.as_mut_ptr() },
+        args:       ptr::null_mut(),
+    };
+    match raw::set_command_info(command, &info) {
+        Some(raw::Status::Err) => Err(error!("Error while setting command info, invalid arity or key spec")),
+        _ => Ok(()),
+    }
+}
+
+fn command_info_version() -> raw::RedisModuleCommandInfoVersion {
+    raw::RedisModuleCommandInfoVersion {
+        version:             raw::REDISMODULE_COMMAND_INFO_VERSION,
+        sizeof_historyentry: mem::size_of::<raw::RedisModuleCommandHistoryEntry>(),
+        sizeof_keyspec:      mem::size_of::<raw::RedisModuleCommandKeySpec>(),
+        sizeof_arg:          mem::size_of::<raw::RedisModuleCommandArg>(),
+    }
+}
+
+fn to_raw_key_spec(spec: &KeySpec, keyword: &Option<CString>) -> raw::RedisModuleCommandKeySpec {
+    let (begin_search_type, bs) = match spec.begin_search {
+        BeginSearch::Index(pos) => (
+            raw::KeySpecBeginSearchType::Index,
+            raw::KeySpecBeginSearch {
+                index: raw::KeySpecBeginSearchIndex { pos },
+            },
+        ),
+        BeginSearch::Keyword { startfrom, .. } => (
+            raw::KeySpecBeginSearchType::Keyword,
+            raw::KeySpecBeginSearch {
+                keyword: raw::KeySpecBeginSearchKeyword {
+                    keyword: keyword.as_ref().map_or(ptr::null(), |k| k.as_ptr()),
+                    startfrom,
+                },
+            },
+        ),
+    };
+    let (find_keys_type, fk) = match spec.find_keys {
+        FindKeys::Range { lastkey, keystep, limit } => (
+            raw::KeySpecFindKeysType::Range,
+            raw::KeySpecFindKeys {
+                range: raw::KeySpecFindKeysRange { lastkey, keystep, limit },
+            },
+        ),
+        FindKeys::Keynum { keynumidx, firstkey, keystep } => (
+            raw::KeySpecFindKeysType::Keynum,
+            raw::KeySpecFindKeys {
+                keynum: raw::KeySpecFindKeysKeynum { keynumidx, firstkey, keystep },
+            },
+        ),
+    };
+    raw::RedisModuleCommandKeySpec {
+        notes: ptr::null(),
+        flags: spec.flags.bits(),
+        begin_search_type,
+        bs,
+        find_keys_type,
+        fk,
+    }
+}
+
+fn to_raw_key_spec_terminator() -> raw::RedisModuleCommandKeySpec {
+    raw::RedisModuleCommandKeySpec {
+        notes:             ptr::null(),
+        flags:             0,
+        begin_search_type: raw::KeySpecBeginSearchType::Invalid,
+        bs:                raw::KeySpecBeginSearch {
+            index: raw::KeySpecBeginSearchIndex { pos: 0 },
+        },
+        find_keys_type:    raw::KeySpecFindKeysType::Omitted,
+        fk:                raw::KeySpecFindKeys {
+            range: raw::KeySpecFindKeysRange { lastkey: 0, keystep: 0, limit: 0 },
+        },
+    }
+}
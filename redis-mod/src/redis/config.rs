@@ -0,0 +1,418 @@
+use crate::error::RModError;
+use crate::redis::{raw, Redis};
+use libc::{c_int, c_longlong};
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+type GetBoolFn = Box<dyn Fn(&str) -> bool>;
+type SetBoolFn = Box<dyn Fn(&str, bool) -> Result<(), RModError>>;
+type GetNumericFn = Box<dyn Fn(&str) -> i64>;
+type SetNumericFn = Box<dyn Fn(&str, i64) -> Result<(), RModError>>;
+type GetStringFn = Box<dyn Fn(&str) -> *mut raw::RedisModuleString>;
+type SetStringFn = Box<dyn Fn(&str, &str) -> Result<(), RModError>>;
+type ApplyFn = Box<dyn Fn(&Redis) -> Result<(), RModError>>;
+
+// The callbacks of a config, handed to Redis as its private data.
+struct Callbacks<G, S> {
+    get:   G,
+    set:   S,
+    apply: Option<ApplyFn>,
+}
+
+enum Entry {
+    Bool {
+        name:      String,
+        default:   bool,
+        flags:     raw::ConfigFlags,
+        callbacks: Callbacks<GetBoolFn, SetBoolFn>,
+    },
+    Numeric {
+        name:      String,
+        default:   i64,
+        min:       i64,
+        max:       i64,
+        flags:     raw::ConfigFlags,
+        callbacks: Callbacks<GetNumericFn, SetNumericFn>,
+    },
+    String {
+        name:      String,
+        default:   String,
+        flags:     raw::ConfigFlags,
+        callbacks: Callbacks<GetStringFn, SetStringFn>,
+    },
+}
+
+impl Entry {
+    fn apply_mut(&mut self) -> &mut Option<ApplyFn> {
+        match *self {
+            Entry::Bool { ref mut callbacks, .. } => &mut callbacks.apply,
+            Entry::Numeric { ref mut callbacks, .. } => &mut callbacks.apply,
+            Entry::String { ref mut callbacks, .. } => &mut callbacks.apply,
+        }
+    }
+
+    fn register(self, ctx: *mut raw::RedisModuleCtx) -> Result<(), RModError> {
+        match self {
+            Entry::Bool { name, default, flags, callbacks } => {
+                let cname = CString::new(name.as_str())?;
+                let applyfn = apply_fn_of(&callbacks);
+                let callbacks = Box::into_raw(Box::new(callbacks));
+                let status = raw::register_bool_config(
+                    ctx,
+                    cname.as_ptr(),
+                    default as c_int,
+                    flags.bits(),
+                    get_bool,
+                    set_bool,
+                    applyfn,
+                    callbacks as *mut c_void,
+                );
+                registered(status, callbacks, &name)
+            }
+            Entry::Numeric { name, default, min, max, flags, callbacks } => {
+                let cname = CString::new(name.as_str())?;
+                let applyfn = apply_fn_of(&callbacks);
+                let callbacks = Box::into_raw(Box::new(callbacks));
+                let status = raw::register_numeric_config(
+                    ctx,
+                    cname.as_ptr(),
+                    default,
+                    flags.bits(),
+                    min,
+                    max,
+                    get_numeric,
+                    set_numeric,
+                    applyfn,
+                    callbacks as *mut c_void,
+                );
+                registered(status, callbacks, &name)
+            }
+            Entry::String { name, default, flags, callbacks } => {
+                let cname = CString::new(name.as_str())?;
+                let cdefault = CString::new(default)?;
+                let applyfn = apply_fn_of(&callbacks);
+                let callbacks = Box::into_raw(Box::new(callbacks));
+                let status = raw::register_string_config(
+                    ctx,
+                    cname.as_ptr(),
+                    cdefault.as_ptr(),
+                    flags.bits(),
+                    get_string,
+                    set_string,
+                    applyfn,
+                    callbacks as *mut c_void,
+                );
+                registered(status, callbacks, &name)
+            }
+        }
+    }
+}
+
+/// `ConfigBuilder` registers a module's configs, which show up as
+/// `<module>.<name>` in `CONFIG GET` and `CONFIG SET` and can be given on the
+/// command line or in the config file like any other config.
+///
+/// Each config is read and stored through its `get_fn` and `set_fn`, and
+/// `set_fn` may return an error to reject a value. Callbacks are kept for
+/// the lifetime of the module.
+///
+/// Configs are added with `bool`, `numeric` and `string`, each optionally
+/// followed by `on_apply`, and then registered all at once with `load` (or
+/// `RedisModuleInitializer::load_configs`).
+#[derive(Default)]
+pub struct ConfigBuilder {
+    entries: Vec<Entry>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    pub fn bool<G, S>(
+        mut self,
+        name: &str,
+        default: bool,
+        flags: raw::ConfigFlags,
+        get_fn: G,
+        set_fn: S,
+    ) -> ConfigBuilder
+    where
+        G: Fn(&str) -> bool + 'static,
+        S: Fn(&str, bool) -> Result<(), RModError> + 'static,
+    {
+        self.entries.push(Entry::Bool {
+            name: name.to_string(),
+            default,
+            flags,
+            callbacks: Callbacks {
+                get:   Box::new(get_fn),
+                set:   Box::new(set_fn),
+                apply: None,
+            },
+        });
+        self
+    }
+
+    /// Adds a numeric config. Redis rejects values outside of `min..=max`
+    /// before `set_fn` is called.
+    #[allow(clippy::too_many_arguments)]
+    pub fn numeric<G, S>(
+        mut self,
+        name: &str,
+        default: i64,
+        min: i64,
+        max: i64,
+        flags: raw::ConfigFlags,
+        get_fn: G,
+        set_fn: S,
+    ) -> ConfigBuilder
+    where
+        G: Fn(&str) -> i64 + 'static,
+        S: Fn(&str, i64) -> Result<(), RModError> + 'static,
+    {
+        self.entries.push(Entry::Numeric {
+            name: name.to_string(),
+            default,
+            min,
+            max,
+            flags,
+            callbacks: Callbacks {
+                get:   Box::new(get_fn),
+                set:   Box::new(set_fn),
+                apply: None,
+            },
+        });
+        self
+    }
+
+    pub fn string<G, S>(
+        mut self,
+        name: &str,
+        default: &str,
+        flags: raw::ConfigFlags,
+        get_fn: G,
+        set_fn: S,
+    ) -> ConfigBuilder
+    where
+        G: Fn(&str) -> String + 'static,
+        S: Fn(&str, &str) -> Result<(), RModError> + 'static,
+    {
+        // Redis doesn't free the string returned by the getter, so the last
+        // one is kept here and freed once it's replaced.
+        let last = Cell::new(ptr::null_mut());
+        let get = move |name: &str| {
+            let value = get_fn(name);
+            let value = raw::create_string(ptr::null_mut(), value.as_ptr(), value.len());
+            let previous = last.replace(value);
+            if !previous.is_null() {
+                raw::free_string(ptr::null_mut(), previous);
+            }
+            value
+        };
+        self.entries.push(Entry::String {
+            name: name.to_string(),
+            default: default.to_string(),
+            flags,
+            callbacks: Callbacks {
+                get:   Box::new(get),
+                set:   Box::new(set_fn),
+                apply: None,
+            },
+        });
+        self
+    }
+
+    /// Sets the function that runs once after all of the values of a
+    /// `CONFIG SET` have been set, if it changed the config added last. It's
+    /// the place to rebuild state that depends on the config (e.g. resize a
+    /// cache). Does nothing if no config was added yet.
+    pub fn on_apply<A>(mut self, apply_fn: A) -> ConfigBuilder
+    where
+        A: Fn(&Redis) -> Result<(), RModError> + 'static,
+    {
+        if let Some(entry) = self.entries.last_mut() {
+            *entry.apply_mut() = Some(Box::new(apply_fn));
+        }
+        self
+    }
+
+    /// Registers the configs and applies the values given to them on the
+    /// command line or in the config file, or their defaults otherwise. Must
+    /// be called from within `RedisModule_OnLoad`.
+    pub fn load(self, ctx: *mut raw::RedisModuleCtx) -> Result<(), RModError> {
+        for entry in self.entries {
+            entry.register(ctx)?;
+        }
+        Redis::new(ctx).load_configs()
+    }
+}
+
+impl Redis {
+    /// Registers a numeric module config, which shows up as
+    /// `<module>.<name>` in `CONFIG GET` and `CONFIG SET`. Must be called from
+    /// within `RedisModule_OnLoad`, followed by `load_configs` once every
+    /// config is registered. `ConfigBuilder` does both.
+    ///
+    /// Redis rejects values outside of `min..=max` before `set_fn` is called.
+    /// `get_fn` and `set_fn` read and store the value, and `set_fn` may
+    /// return an error to reject it. `apply_fn` runs once after all of the
+    /// values of a `CONFIG SET` have been set, which makes it the place to
+    /// rebuild state that depends on the config (e.g. resize a cache).
+    ///
+    /// The callbacks are kept for the lifetime of the module.
+    #[allow(clippy::too_many_arguments)]
+    pub fn config_numeric<G, S, A>(
+        &self,
+        name: &str,
+        default: i64,
+        min: i64,
+        max: i64,
+        flags: raw::ConfigFlags,
+        get_fn: G,
+        set_fn: S,
+        apply_fn: A,
+    ) -> Result<(), RModError>
+    where
+        G: Fn(&str) -> i64 + 'static,
+        S: Fn(&str, i64) -> Result<(), RModError> + 'static,
+        A: Fn(&Redis) -> Result<(), RModError> + 'static,
+    {
+        Entry::Numeric {
+            name: name.to_string(),
+            default,
+            min,
+            max,
+            flags,
+            callbacks: Callbacks {
+                get:   Box::new(get_fn),
+                set:   Box::new(set_fn),
+                apply: Some(Box::new(apply_fn)),
+            },
+        }
+        .register(self.ctx)
+    }
+
+    /// Applies the values given to the registered configs on the command
+    /// line or in the config file, or their defaults otherwise. Must be
+    /// called from within `RedisModule_OnLoad` after the last config is
+    /// registered, or Redis refuses to load the module.
+    pub fn load_configs(&self) -> Result<(), RModError> {
+        match raw::load_configs(self.ctx) {
+            Some(status) => super::handle_status(status, "Could not load configs"),
+            None => Err(error!("Module configs require Redis 7 or later")),
+        }
+    }
+}
+
+fn apply_fn_of<G, S>(callbacks: &Callbacks<G, S>) -> Option<raw::RedisModuleConfigApplyFunc> {
+    match callbacks.apply {
+        Some(_) => Some(apply_config::<G, S>),
+        None => None,
+    }
+}
+
+// Maps the status of a registration, taking the callbacks back if Redis
+// didn't take them.
+fn registered<G, S>(
+    status: Option<raw::Status>,
+    callbacks: *mut Callbacks<G, S>,
+    name: &str,
+) -> Result<(), RModError> {
+    match status {
+        Some(raw::Status::Ok) => Ok(()),
+        Some(raw::Status::Err) => {
+            drop(unsafe { Box::from_raw(callbacks) });
+            Err(error!("Could not register config '{}'", name))
+        }
+        None => {
+            drop(unsafe { Box::from_raw(callbacks) });
+            Err(error!("Module configs require Redis 7 or later"))
+        }
+    }
+}
+
+// Hands an error back to Redis, which replies with it and frees the string.
+fn set_config_error(err: *mut *mut raw::RedisModuleString, e: &RModError) {
+    if err.is_null() {
+        return;
+    }
+    let message = e.to_string();
+    unsafe { *err = raw::create_string(ptr::null_mut(), message.as_ptr(), message.len()) };
+}
+
+fn set_result(result: Result<(), RModError>, err: *mut *mut raw::RedisModuleString) -> raw::Status {
+    match result {
+        Ok(()) => raw::Status::Ok,
+        Err(e) => {
+            set_config_error(err, &e);
+            raw::Status::Err
+        }
+    }
+}
+
+fn config_name<'a>(name: *const c_char) -> &'a str {
+    unsafe { CStr::from_ptr(name) }.to_str().unwrap_or("")
+}
+
+extern "C" fn get_bool(name: *const c_char, privdata: *mut c_void) -> c_int {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetBoolFn, SetBoolFn>) };
+    (callbacks.get)(config_name(name)) as c_int
+}
+
+extern "C" fn set_bool(
+    name: *const c_char,
+    val: c_int,
+    privdata: *mut c_void,
+    err: *mut *mut raw::RedisModuleString,
+) -> raw::Status {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetBoolFn, SetBoolFn>) };
+    set_result((callbacks.set)(config_name(name), val != 0), err)
+}
+
+extern "C" fn get_numeric(name: *const c_char, privdata: *mut c_void) -> c_longlong {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetNumericFn, SetNumericFn>) };
+    (callbacks.get)(config_name(name))
+}
+
+extern "C" fn set_numeric(
+    name: *const c_char,
+    val: c_longlong,
+    privdata: *mut c_void,
+    err: *mut *mut raw::RedisModuleString,
+) -> raw::Status {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetNumericFn, SetNumericFn>) };
+    set_result((callbacks.set)(config_name(name), val), err)
+}
+
+extern "C" fn get_string(name: *const c_char, privdata: *mut c_void) -> *mut raw::RedisModuleString {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetStringFn, SetStringFn>) };
+    (callbacks.get)(config_name(name))
+}
+
+extern "C" fn set_string(
+    name: *const c_char,
+    val: *mut raw::RedisModuleString,
+    privdata: *mut c_void,
+    err: *mut *mut raw::RedisModuleString,
+) -> raw::Status {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<GetStringFn, SetStringFn>) };
+    let result = super::manifest_redis_string(val)
+        .map_err(RModError::from)
+        .and_then(|val| (callbacks.set)(config_name(name), val.as_str()));
+    set_result(result, err)
+}
+
+extern "C" fn apply_config<G, S>(
+    ctx: *mut raw::RedisModuleCtx,
+    privdata: *mut c_void,
+    err: *mut *mut raw::RedisModuleString,
+) -> raw::Status {
+    let callbacks = unsafe { &*(privdata as *const Callbacks<G, S>) };
+    match callbacks.apply {
+        Some(ref apply) => set_result(apply(&Redis::new(ctx)), err),
+        None => raw::Status::Ok,
+    }
+}
@@ -0,0 +1,92 @@
+use crate::error::RModError;
+use crate::redis::{handle_status, raw, Redis, RedisCallReply};
+use libc::c_long;
+
+/// `ArrayLenHandle` is an array reply whose length is only set once all of
+/// its elements have been replied, for commands that can't know the number
+/// of elements up front.
+///
+/// Elements replied through `reply_element` are counted, and `finish` sets
+/// the length to that count. Elements can also be replied directly through
+/// `Redis`, in which case `set_len` has to be given their number. Either way
+/// the handle must be finished on every path, including early returns with
+/// `?`: when it's dropped unfinished, the length is set to the counted
+/// elements only, which breaks the reply if any were replied directly.
+pub struct ArrayLenHandle<'a> {
+    redis:    &'a Redis,
+    len:      c_long,
+    finished: bool,
+}
+
+impl<'a> ArrayLenHandle<'a> {
+    /// Replies with a single element of the array through `reply`, which may
+    /// itself reply with a nested array.
+    pub fn reply_element<F>(&mut self, reply: F) -> Result<(), RModError>
+    where
+        F: FnOnce(&Redis) -> Result<(), RModError>,
+    {
+        reply(self.redis)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Calls a Redis command in between elements, like `Redis::call`.
+    /// Calling through `Redis` works just as well, since calls don't reply to
+    /// the client.
+    pub fn call(&self, cmdname: &str, args: &[&str]) -> Result<RedisCallReply, RModError> {
+        self.redis.call(cmdname, args)
+    }
+
+    /// Returns the number of elements replied so far.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Sets the length of the array to the number of elements replied
+    /// through `reply_element`.
+    pub fn finish(mut self) {
+        self.set_array_length();
+    }
+
+    /// Sets the length of the array to `n`, which must be the total number of
+    /// elements replied, both directly and through `reply_element`.
+    pub fn set_len(mut self, n: usize) {
+        self.len = n as c_long;
+        self.set_array_length();
+    }
+
+    fn set_array_length(&mut self) {
+        if !self.finished {
+            raw::reply_set_array_length(self.redis.ctx, self.len);
+            self.finished = true;
+        }
+    }
+}
+
+impl<'a> Drop for ArrayLenHandle<'a> {
+    // Sets the length of the array if `finish` wasn't called.
+    fn drop(&mut self) {
+        self.set_array_length();
+    }
+}
+
+impl Redis {
+    /// Starts an array reply whose length is set later through the returned
+    /// `ArrayLenHandle`.
+    pub fn reply_array_deferred(&self) -> Result<ArrayLenHandle<'_>, RModError> {
+        self.mark_replied();
+        handle_status(
+            raw::reply_with_array(self.ctx, raw::REDISMODULE_POSTPONED_ARRAY_LEN),
+            "Could not reply with deferred array",
+        )?;
+        Ok(ArrayLenHandle {
+            redis:    self,
+            len:      0,
+            finished: false,
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use crate::error::RModError;
+use crate::redis::{raw, Redis};
+use libc::c_int;
+use std::ffi::CString;
+use std::sync::OnceLock;
+
+/// The function a module registers to add its own sections to `INFO`. The
+/// flag tells whether the info is being collected for a crash report.
+pub type InfoFunc = fn(&mut InfoContext, bool);
+
+// Redis passes no private data to the info callback, so the function is kept
+// here for the trampoline. A module only registers one.
+static INFO_FUNC: OnceLock<InfoFunc> = OnceLock::new();
+
+/// `InfoContext` is given to an `InfoFunc` to add sections and fields to the
+/// output of `INFO`.
+///
+/// Redis prefixes section names with the module name, so a section `stats`
+/// of a module `mymod` shows up as `# mymod_stats`. Fields must be added
+/// after the section they belong to.
+pub struct InfoContext {
+    ctx: *mut raw::RedisModuleInfoCtx,
+}
+
+impl InfoContext {
+    /// Starts a new section. Errors when `INFO` didn't ask for the section,
+    /// in which case adding its fields can be skipped since they wouldn't be
+    /// shown anyway.
+    pub fn add_section(&mut self, name: &str) -> Result<(), RModError> {
+        let name = CString::new(name)?;
+        match raw::info_add_section(self.ctx, name.as_ptr()) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => Err(error!("Could not add section to INFO, not requested")),
+        }
+    }
+
+    pub fn add_field_long_long(&mut self, field: &str, value: i64) -> Result<(), RModError> {
+        let field = CString::new(field)?;
+        match raw::info_add_field_long_long(self.ctx, field.as_ptr(), value) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => Err(error!("Could not add field to INFO")),
+        }
+    }
+
+    pub fn add_field_str(&mut self, field: &str, value: &str) -> Result<(), RModError> {
+        let field = CString::new(field)?;
+        let value = CString::new(value)?;
+        match raw::info_add_field_cstring(self.ctx, field.as_ptr(), value.as_ptr()) {
+            Some(raw::Status::Ok) => Ok(()),
+            _ => Err(error!("Could not add field to INFO")),
+        }
+    }
+}
+
+impl Redis {
+    /// Registers the function that adds the module's sections to `INFO`.
+    /// Must be called from within `RedisModule_OnLoad`, and only once.
+    pub fn register_info_func(&self, info_func: InfoFunc) -> Result<(), RModError> {
+        if INFO_FUNC.get().is_some() {
+            return Err(error!("An info function is already registered"));
+        }
+        match raw::register_info_func(self.ctx, info_callback) {
+            Some(status) => super::handle_status(status, "Could not register info function")?,
+            None => return Err(error!("Info functions require Redis 6 or later")),
+        }
+        // Only set once registered, so that a failed registration can be
+        // retried. Loading runs on the main thread, so there's no race.
+        let _ = INFO_FUNC.set(info_func);
+        Ok(())
+    }
+}
+
+extern "C" fn info_callback(ctx: *mut raw::RedisModuleInfoCtx, for_crash_report: c_int) {
+    if let Some(info_func) = INFO_FUNC.get() {
+        info_func(&mut InfoContext { ctx }, for_crash_report != 0);
+    }
+}
@@ -7,17 +7,67 @@
            allow(redundant_field_names, suspicious_arithmetic_impl))]
 pub mod raw;
 
+mod blocked_client;
+pub use self::blocked_client::{BlockedClient, ReplyChannel};
+
+mod cluster;
+pub use self::cluster::ClusterMessageReceiver;
+
+mod command_filter;
+pub use self::command_filter::{CommandFilter, CommandFilterCtx};
+
+mod command_info;
+pub use self::command_info::{BeginSearch, FindKeys, KeySpecBuilder};
+
+mod config;
+pub use self::config::ConfigBuilder;
+
+mod deferred_array;
+pub use self::deferred_array::ArrayLenHandle;
+
+mod info;
+pub use self::info::{InfoContext, InfoFunc};
+
+mod module_type;
+pub use self::module_type::{DataType, ModuleDigest, ModuleIO, ModuleType, ModuleTypeBuilder};
+
+mod scan;
+pub use self::scan::{PageToken, PaginatedReply, ScanCursor};
+
+mod thread_safe_context;
+pub use self::thread_safe_context::{LockedCallReply, ThreadSafeContext, ThreadSafeContextGuard};
+
+mod timer;
+pub use self::timer::TimerId;
+
+mod type_registry;
+pub use self::type_registry::TypeRegistry;
+
+mod worker_pool;
+pub use self::worker_pool::WorkerPool;
+
 use crate::error::RModError;
 use libc::{c_int, c_long, c_longlong, size_t};
 use std::ptr;
+use std::rc::Rc;
 use std::string;
 use time;
-use std::ffi::CString;
+use std::cell::{Cell, RefCell};
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::{c_char, c_void};
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
 
 static AB: AtomicBool = AtomicBool::new(false);
 
+thread_local! {
+    // The name the running command was invoked with (`argv[0]`), which is
+    // what `current_command_name` falls back to on servers before Redis 7.
+    static CURRENT_COMMAND: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 #[global_allocator]
 static RA: RedisAlloc = RedisAlloc;
 
@@ -31,24 +81,72 @@ pub enum LogLevel {
     Warning,
 }
 
+impl LogLevel {
+    /// Returns the level as it's named by the Redis module API.
+    pub fn as_str(&self) -> &'static str {
+        let level = self.as_c_str();
+        &level[..level.len() - 1]
+    }
+
+    /// Detects whether messages at this level are logged at all. Debug
+    /// messages are left out of release builds, and the others are all
+    /// passed on to Redis, which filters them by its `loglevel`.
+    pub fn is_enabled(&self) -> bool {
+        match *self {
+            LogLevel::Debug => cfg!(debug_assertions),
+            _ => true,
+        }
+    }
+
+    // Same as `as_str`, but NUL-terminated to be passed to Redis as is.
+    fn as_c_str(&self) -> &'static str {
+        match *self {
+            LogLevel::Debug => "debug\0",
+            LogLevel::Notice => "notice\0",
+            LogLevel::Verbose => "verbose\0",
+            LogLevel::Warning => "warning\0",
+        }
+    }
+}
+
 /// Reply represents the various types of a replies that we can receive after
 /// executing a Redis command.
 #[derive(Debug)]
 pub enum Reply {
-    Array,
+    Array(Vec<Reply>),
+    /// Sent as `1` or `0` to clients speaking RESP2.
+    Bool(bool),
+    /// Sent as a bulk string to clients speaking RESP2.
+    Double(f64),
     Error,
     Integer(i64),
+    /// Key and value pairs. Keys and values may themselves be arrays or maps.
+    Map(Vec<(Reply, Reply)>),
     Nil,
     String(String),
     Unknown,
 }
 
 pub trait Command {
-    // Should return the name of the command to be registered.
+    // Should return the name of the command to be registered. For a
+    // subcommand, this is only its own name (e.g. `get` for `mymod get`).
     fn name(&self) -> &'static str;
 
-    // Run the command.
-    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError>;
+    // Run the command. Commands reply by themselves through `r`, or
+    // implement `run_reply` instead and leave this as it is.
+    fn run(&self, r: Redis, args: &[&str]) -> Result<(), RModError> {
+        let (ctx, replied) = (r.ctx, Rc::clone(&r.replied));
+        let reply = self.run_reply(r, args)?;
+        Redis { ctx, replied }.reply(&reply)
+    }
+
+    /// Runs the command and returns the value to reply with, which is sent
+    /// back by `run` (including any nested arrays and maps). This saves
+    /// commands from calling the `reply_*` functions themselves, which is
+    /// easy to get wrong when replying with an array.
+    fn run_reply(&self, _r: Redis, _args: &[&str]) -> Result<Reply, RModError> {
+        Err(error!("Command implements neither run nor run_reply"))
+    }
 
     // Should return any flags to be registered with the name as a string
     // separated list. See the latest Redis module API documentation for a complete
@@ -69,6 +167,32 @@ pub trait Command {
     ///     "getkeys-api": The command implements the interface to return the arguments that are keys. Used when start/stop/step is not enough because of the command syntax.
     ///     "no-cluster": The command should not register in Redis Cluster since is not designed to work with it. 
     fn str_flags(&self) -> &'static str;  
+
+    /// Should return the `(firstkey, lastkey, keystep)` positions of the keys
+    /// in the command's arguments, which Redis Cluster uses to route the
+    /// command. `lastkey` may be negative to count from the end (`-1` is the
+    /// last argument). Commands taking no keys use the default `(0, 0, 0)`.
+    fn key_spec(&self) -> (i32, i32, i32) {
+        (0, 0, 0)
+    }
+
+    /// Should return the number of arguments the command takes, including
+    /// the command name, like the arity reported by `COMMAND INFO`. A
+    /// negative arity `-N` means at least `N` arguments, and the default of
+    /// `0` leaves the number of arguments unchecked. The arguments of a
+    /// subcommand include both its parent's name and its own.
+    ///
+    /// Redis enforces it before the command runs as of Redis 7, and `harness`
+    /// checks it on older servers.
+    fn arity(&self) -> i32 {
+        0
+    }
+
+    /// Key specs to be set on the command once it's registered, for commands
+    /// whose keys can't be described by a plain range of arguments.
+    fn key_specs(&self) -> Option<KeySpecBuilder> {
+        None
+    }
 }
 
 impl dyn Command {
@@ -80,89 +204,272 @@ impl dyn Command {
         argv: *mut *mut raw::RedisModuleString,
         argc: c_int,
     ) -> raw::Status {
-        let r = Redis { ctx };
+        let r = Redis::new(ctx);
+        let replied = Rc::clone(&r.replied);
+        if !arity_matches(command.arity(), argc) {
+            let message = format!("wrong number of arguments for '{}' command", command.name());
+            return handle_run_result(ctx, Err(RModError::with_code("ERR", message.as_str())), false);
+        }
         let args = parse_args(argv, argc).unwrap();
         let str_args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
         raw::auto_memory(ctx);
-        match command.run(r, str_args.as_slice()) {
-            Ok(_) => raw::Status::Ok,
-            Err(e) => {
-                raw::reply_with_error(
-                    ctx,
-                    format!("RMod error: {}\0", e.to_string()).as_ptr(),
-                );
-                raw::Status::Err
-            }
+        CURRENT_COMMAND.with(|name| *name.borrow_mut() = args.first().cloned());
+        let result = command.run(r, str_args.as_slice());
+        CURRENT_COMMAND.with(|name| *name.borrow_mut() = None);
+        handle_run_result(ctx, result, replied.get())
+    }
+
+    /// Sets the arity and key specs declared by the command on the already
+    /// created command, so that Redis enforces and reports them. Does nothing
+    /// if the command declares neither.
+    pub fn set_command_info(
+        command: &dyn Command,
+        ctx: *mut raw::RedisModuleCtx,
+    ) -> Result<(), RModError> {
+        set_command_info_as(command, ctx, command.name())
+    }
+
+    /// Creates `command` as a subcommand of the already created `parent`,
+    /// invoked as `<parent> <command>`, and sets its command info. A parent
+    /// with subcommands is created without a handler of its own.
+    ///
+    /// Subcommands only exist as of Redis 7, so this errors on older servers.
+    pub fn create_subcommand(
+        parent: &dyn Command,
+        command: &dyn Command,
+        cmdfunc: raw::RedisModuleCmdFunc,
+        ctx: *mut raw::RedisModuleCtx,
+    ) -> Result<(), RModError> {
+        let parent_name = CString::new(parent.name())?;
+        let parent_command = match raw::get_command(ctx, parent_name.as_ptr()) {
+            Some(parent_command) if !parent_command.is_null() => parent_command,
+            Some(_) => return Err(error!("Error while creating subcommand, no such parent command")),
+            None => return Err(error!("Subcommands require Redis 7 or later")),
+        };
+
+        let (firstkey, lastkey, keystep) = command.key_spec();
+        let status = raw::create_subcommand(
+            parent_command,
+            format!("{}\0", command.name()).as_ptr(),
+            Some(cmdfunc),
+            format!("{}\0", command.str_flags()).as_ptr(),
+            firstkey,
+            lastkey,
+            keystep,
+        );
+        match status {
+            Some(raw::Status::Ok) => (),
+            _ => return Err(error!("Error while creating subcommand {}", command.name())),
+        }
+
+        let full_name = format!("{}|{}", parent.name(), command.name());
+        set_command_info_as(command, ctx, full_name.as_str())
+    }
+}
+
+fn set_command_info_as(
+    command: &dyn Command,
+    ctx: *mut raw::RedisModuleCtx,
+    name: &str,
+) -> Result<(), RModError> {
+    let key_specs = command.key_specs();
+    if command.arity() == 0 && key_specs.is_none() {
+        return Ok(());
+    }
+    command_info::set_command_info(ctx, name, command.arity(), key_specs.as_ref())
+}
+
+// Detects whether `argc` arguments (including the command name) satisfy the
+// arity of a command, following the same convention as Redis.
+fn arity_matches(arity: i32, argc: c_int) -> bool {
+    match arity {
+        0 => true,
+        arity if arity > 0 => argc == arity,
+        arity => argc >= -arity,
+    }
+}
+
+// Detects whether every part of `reply` carries something to reply with.
+fn check_replyable(reply: &Reply) -> Result<(), RModError> {
+    match *reply {
+        Reply::Array(ref elements) => elements.iter().try_for_each(check_replyable),
+        Reply::Map(ref pairs) => pairs.iter().try_for_each(|(key, value)| {
+            check_replyable(key)?;
+            check_replyable(value)
+        }),
+        Reply::Error | Reply::Unknown => {
+            Err(error!("Could not reply with {:?}, no value to reply with", reply))
         }
+        _ => Ok(()),
     }
 }
 
+// Handles the OK/ERR reply back to Redis for the result of running a command,
+// or of any callback that completes one (like a blocked client's reply).
+//
+// An error is a client error as long as it can be replied: the command has
+// then completed as far as Redis is concerned, so the status is still `Ok`.
+// Returning `Err` once a reply was sent can make Redis reply a second time.
+//
+// If the command already replied before failing, replying the error too
+// would send the client one reply more than it asked for, so the error is
+// only logged and the earlier reply stands. Only when the error can't be
+// replied at all, leaving the client without a reply, is it a true failure
+// that's returned as `Err`.
+fn handle_run_result(
+    ctx: *mut raw::RedisModuleCtx,
+    result: Result<(), RModError>,
+    replied: bool,
+) -> raw::Status {
+    let e = match result {
+        Ok(()) => return raw::Status::Ok,
+        Err(e) => e,
+    };
+    let r = Redis::new(ctx);
+    if replied {
+        r.log(LogLevel::Warning, format!("Command failed after replying: {}", e).as_str());
+        return raw::Status::Ok;
+    }
+    let status = reply_with_rmod_error(ctx, &e);
+    if status == raw::Status::Err {
+        r.log(LogLevel::Warning, format!("Could not reply with command error: {}", e).as_str());
+    }
+    status
+}
+
+// Errors carrying a Redis error code are replied as they are, and everything
+// else gets the generic `ERR` code. Causes of the error are appended, so the client
+// sees more than the outermost message.
+fn reply_with_rmod_error(ctx: *mut raw::RedisModuleCtx, e: &RModError) -> raw::Status {
+    raw::reply_with_error(ctx, format!("{}\0", rmod_error_message(e)).as_ptr())
+}
+
+// Detects whether `code` can start an error reply, e.g. `WRONGTYPE`.
+fn is_error_code(code: &str) -> bool {
+    !code.is_empty() && code.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+}
+
+fn rmod_error_message(e: &RModError) -> String {
+    let mut message = match e.code() {
+        Some(_) => e.to_string(),
+        None => format!("ERR {}", e),
+    };
+    // Every variant is a wrapper whose source is the error it wraps, which
+    // displays the same, so causes start below it.
+    let mut cause = e.inner().source();
+    while let Some(err) = cause {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        cause = match err.downcast_ref::<RModError>() {
+            Some(e) => e.inner().source(),
+            None => err.source(),
+        };
+    }
+    message
+}
+
+// The key popped from along with the popped elements, if any.
+type PopResult<T> = Result<Option<(String, Vec<T>)>, RModError>;
+
+/// The mode and flags a key is opened with by `Redis::open_key_with_flags`.
+pub type OpenKeyFlags = raw::KeyMode;
+
+/// A stream entry ID along with its field/value pairs.
+pub type StreamEntry = (String, Vec<(String, String)>);
+
 /// Redis is a structure that's designed to give us a high-level interface to
 /// the Redis module API by abstracting away the raw C FFI calls.
 pub struct Redis {
     ctx: *mut raw::RedisModuleCtx,
+
+    // Whether anything was replied through this context. It's shared with
+    // the harness, which hands the `Redis` itself over to `Command::run`.
+    replied: Rc<Cell<bool>>,
 }
 
 impl Redis {
-        pub fn call2_reply_int(&self, cmdname: &str, args0: &str, args1: &str) -> c_longlong {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let key = CString::new(args0).expect("CString::new(key) failed");
-            let arg0 = CString::new(args1).expect("CString::new(arg0) failed");
-            raw::callable2_reply_int(self.ctx, cmdname.as_ptr(), key.as_ptr(), arg0.as_ptr())
+        /// Wraps a raw context given to us by Redis, such as the one passed to
+        /// a callback.
+        ///
+        /// # Safety
+        ///
+        /// `ctx` must be a valid context and must outlive the returned value.
+        pub unsafe fn from_ctx(ctx: *mut raw::RedisModuleCtx) -> Redis {
+            Redis::new(ctx)
+        }
+
+        fn new(ctx: *mut raw::RedisModuleCtx) -> Redis {
+            Redis {
+                ctx,
+                replied: Rc::new(Cell::new(false)),
+            }
+        }
+
+        // Records that a reply was sent, so that the harness doesn't reply
+        // an error on top of it.
+        fn mark_replied(&self) {
+            self.replied.set(true);
+        }
+
+        pub fn call2_reply_int(&self, cmdname: &str, args0: &str, args1: &str) -> Result<c_longlong, RModError> {
+            let cmdname = CString::new(cmdname)?;
+            let key = CString::new(args0)?;
+            let arg0 = CString::new(args1)?;
+            Ok(raw::callable2_reply_int(self.ctx, cmdname.as_ptr(), key.as_ptr(), arg0.as_ptr()))
         }
 
         pub fn call1_reply_integer(&self, cmdname: &str, arg0 : &str) -> Result<i64, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg = CString::new(arg0).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg = CString::new(arg0)?;
             let reply = RedisCallReply::create(raw::call1_reply(self.ctx, cmdname.as_ptr(),arg.as_ptr()));
             reply.to_integer()
         }
 
         pub fn call2_reply_integer(&self, cmdname: &str, arg0 : &str, arg1 : &str) -> Result<i64, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg0 = CString::new(arg0).expect("CString::new(arg) failed");
-            let arg1 = CString::new(arg1).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg0 = CString::new(arg0)?;
+            let arg1 = CString::new(arg1)?;
             let reply = RedisCallReply::create(raw::call2_reply(self.ctx, cmdname.as_ptr(),arg0.as_ptr(), arg1.as_ptr()));
             reply.to_integer()
         }
 
         pub fn call3_reply_integer(&self, cmdname: &str, arg0 : &str, arg1 : &str, arg2 : &str) -> Result<i64, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg0 = CString::new(arg0).expect("CString::new(arg) failed");
-            let arg1 = CString::new(arg1).expect("CString::new(arg) failed");
-            let arg2 = CString::new(arg2).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg0 = CString::new(arg0)?;
+            let arg1 = CString::new(arg1)?;
+            let arg2 = CString::new(arg2)?;
             let reply = RedisCallReply::create(raw::call3_reply(self.ctx, cmdname.as_ptr(),arg0.as_ptr(), arg1.as_ptr(), arg2.as_ptr()));
             reply.to_integer()
         }
 
         pub fn call1_reply_string(&self, cmdname: &str, arg0 : &str) -> Result<String, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg = CString::new(arg0).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg = CString::new(arg0)?;
             let reply = RedisCallReply::create(raw::call1_reply(self.ctx, cmdname.as_ptr(),arg.as_ptr()));
             reply.to_string()
         }
 
         pub fn call2_reply_string(&self, cmdname: &str, arg0 : &str, arg1 : &str) -> Result<String, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg0 = CString::new(arg0).expect("CString::new(arg) failed");
-            let arg1 = CString::new(arg1).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg0 = CString::new(arg0)?;
+            let arg1 = CString::new(arg1)?;
             let reply = RedisCallReply::create(raw::call2_reply(self.ctx, cmdname.as_ptr(), arg0.as_ptr(), arg1.as_ptr()));
             reply.to_string()
         }
 
         pub fn call3_reply_string(&self, cmdname: &str, arg0 : &str, arg1 : &str, arg2 : &str) -> Result<String, RModError> {
-            let cmdname = CString::new(cmdname).expect("CString::new(cmdname) failed");
-            let arg0 = CString::new(arg0).expect("CString::new(arg) failed");
-            let arg1 = CString::new(arg1).expect("CString::new(arg) failed");
-            let arg2 = CString::new(arg2).expect("CString::new(arg) failed");
+            let cmdname = CString::new(cmdname)?;
+            let arg0 = CString::new(arg0)?;
+            let arg1 = CString::new(arg1)?;
+            let arg2 = CString::new(arg2)?;
             let reply = RedisCallReply::create(raw::call3_reply(self.ctx, cmdname.as_ptr(),arg0.as_ptr(), arg1.as_ptr(), arg2.as_ptr()));
             reply.to_string()
         }
 
 
         pub fn call_keys(&self, arg: &str) -> Result<Vec<String>, RModError> {
-            let arg = CString::new(arg).expect("CString::new(arg) failed");
-            let cmd = CString::new("keys").expect("CString::new(keys) failed");
+            let arg = CString::new(arg)?;
+            let cmd = CString::new("keys")?;
             let reply = RedisCallReply::create(raw::call1_reply(self.ctx, cmd.as_ptr(), arg.as_ptr()));
             let size = reply.check_length() as u64;
             let mut vec_keys: Vec<String> = Vec::with_capacity(size as usize);
@@ -180,6 +487,308 @@ impl Redis {
             Ok(vec_keys)
         }
 
+        /// Calls a Redis command with any number of arguments and returns its
+        /// reply. An error reply from the command becomes an `Err` carrying
+        /// its error code and message.
+        ///
+        /// The command isn't propagated to the AOF or replicas. A command
+        /// writing through it (or through the helpers built on it) should
+        /// replicate itself with `replicate_verbatim`, or use
+        /// `call_replicated` when replicating the called command is enough.
+        pub fn call(&self, cmdname: &str, args: &[&str]) -> Result<RedisCallReply, RModError> {
+            self.call_with(cmdname, args, raw::call_argv)
+        }
+
+        /// Same as `call`, but the command is also propagated to the AOF and
+        /// replicas as it is, so there's no need to replicate it separately.
+        pub fn call_replicated(&self, cmdname: &str, args: &[&str]) -> Result<RedisCallReply, RModError> {
+            self.call_with(cmdname, args, raw::call_argv_replicate)
+        }
+
+        /// Restores a value serialized by `RedisKeyWritable::dump` into `key`,
+        /// like `RESTORE`, optionally with a time to live. Errors with
+        /// `BUSYKEY` if the key already exists.
+        ///
+        /// This goes by the key's name rather than through an open key,
+        /// since an open key wouldn't see the restored value. Not replicated,
+        /// see `call`.
+        pub fn restore(&self, key: &str, payload: &[u8], ttl: Option<time::Duration>) -> Result<(), RModError> {
+            let ttl = ttl.map_or(0, |ttl| ttl.num_milliseconds()).to_string();
+            self.call_with("restore", &[key.as_bytes(), ttl.as_bytes(), payload], raw::call_argv)?;
+            Ok(())
+        }
+
+        fn call_with<A: AsRef<[u8]>>(
+            &self,
+            cmdname: &str,
+            args: &[A],
+            call_argv: unsafe fn(*mut raw::RedisModuleCtx, *const i8, *mut *mut raw::RedisModuleString, size_t) -> *mut raw::RedisModuleCallReply,
+        ) -> Result<RedisCallReply, RModError> {
+            let cmdname = CString::new(cmdname)?;
+            let redis_args: Vec<RedisString> =
+                args.iter().map(|a| RedisString::create_from_bytes(self.ctx, a.as_ref())).collect();
+            let mut argv: Vec<*mut raw::RedisModuleString> = redis_args.iter().map(|a| a.str_inner).collect();
+            // `cmdname` and `argv` both outlive the call.
+            let reply = unsafe { call_argv(self.ctx, cmdname.as_ptr(), argv.as_mut_ptr(), argv.len()) };
+            if reply.is_null() {
+                return Err(error!("Failed to call command, unknown command or wrong arity"))
+            }
+            let reply = RedisCallReply::create(reply);
+            if reply.check_type() == raw::ReplyType::Error {
+                return Err(parse_error_reply(&reply.to_error()?));
+            }
+            Ok(reply)
+        }
+
+        /// Pops up to `count` elements from the first non-empty list among
+        /// `keys`, like `LMPOP`, returning the key popped from along with the
+        /// elements. Returns `None` when all the lists are empty.
+        ///
+        /// The pop is replicated as an `LMPOP` on its own.
+        pub fn lmpop(&self, keys: &[&str], from_left: bool, count: usize) -> PopResult<String> {
+            let direction = if from_left { "left" } else { "right" };
+            let reply = match self.call_mpop("lmpop", keys, direction, count)? {
+                Some(reply) => reply,
+                None => return Ok(None),
+            };
+
+            let key = reply.reply_array_element(0)?.to_string()?;
+            let elements_reply = reply.reply_array_element(1)?;
+            let size = elements_reply.check_length();
+            let mut elements: Vec<String> = Vec::with_capacity(size);
+            for idx in 0..size {
+                elements.push(elements_reply.reply_array_element(idx)?.to_string()?);
+            }
+            Ok(Some((key, elements)))
+        }
+
+        /// Pops up to `count` members from the first non-empty sorted set
+        /// among `keys`, like `ZMPOP`, returning the key popped from along
+        /// with the member/score pairs. Returns `None` when all the sorted
+        /// sets are empty.
+        ///
+        /// The pop is replicated as a `ZMPOP` on its own.
+        pub fn zmpop(&self, keys: &[&str], from_min: bool, count: usize) -> PopResult<(String, f64)> {
+            let direction = if from_min { "min" } else { "max" };
+            let reply = match self.call_mpop("zmpop", keys, direction, count)? {
+                Some(reply) => reply,
+                None => return Ok(None),
+            };
+
+            let key = reply.reply_array_element(0)?.to_string()?;
+            let members_reply = reply.reply_array_element(1)?;
+            let size = members_reply.check_length();
+            let mut members: Vec<(String, f64)> = Vec::with_capacity(size);
+            for idx in 0..size {
+                let pair = members_reply.reply_array_element(idx)?;
+                let member = pair.reply_array_element(0)?.to_string()?;
+                let score = match pair.reply_array_element(1)?.to_string()?.parse::<f64>() {
+                    Ok(score) => score,
+                    Err(_) => return Err(error!("Failed to parse score from zmpop reply")),
+                };
+                members.push((member, score));
+            }
+            Ok(Some((key, members)))
+        }
+
+        /// Calls each of the given commands in order, collecting their replies.
+        /// A failing command doesn't stop the batch; its error is collected
+        /// in its place instead, including error replies from Redis.
+        pub fn call_batch(&self, calls: &[(&str, Vec<&str>)]) -> Vec<Result<Reply, RModError>> {
+            calls
+                .iter()
+                .map(|(cmdname, args)| self.call(cmdname, args.as_slice())?.to_reply())
+                .collect()
+        }
+
+        /// Renames the key `from` to `to`, like `RENAME`, overwriting `to` if
+        /// it already exists. Errors with the error replied by `RENAME`, e.g.
+        /// when `from` doesn't exist.
+        pub fn rename_key(&self, from: &str, to: &str) -> Result<(), RModError> {
+            self.call("rename", &[from, to])?;
+            Ok(())
+        }
+
+        /// Adds `member` to the set at `key`, like `SADD`. Returns whether it
+        /// was added, i.e. it wasn't a member already.
+        pub fn sadd(&self, key: &str, member: &str) -> Result<bool, RModError> {
+            self.call_bool("sadd", &[key, member])
+        }
+
+        /// Detects whether `member` belongs to the set at `key`, like
+        /// `SISMEMBER`.
+        pub fn sismember(&self, key: &str, member: &str) -> Result<bool, RModError> {
+            self.call_bool("sismember", &[key, member])
+        }
+
+        /// Pops an element from one end of the list at `src` and pushes it to
+        /// one end of the list at `dst` atomically, like `LMOVE`, returning the
+        /// moved element. Returns `None` when `src` is empty.
+        ///
+        /// The move is replicated as an `LMOVE` on its own.
+        pub fn lmove(&self, src: &str, dst: &str, from_left: bool, to_left: bool) -> Result<Option<String>, RModError> {
+            let from = if from_left { "left" } else { "right" };
+            let to = if to_left { "left" } else { "right" };
+            match self.call_replicated("lmove", &[src, dst, from, to])?.to_reply()? {
+                Reply::String(ele) => Ok(Some(ele)),
+                Reply::Nil => Ok(None),
+                _ => Err(error!("Invalid type of CallReply, not String")),
+            }
+        }
+
+        fn call_bool(&self, cmdname: &str, args: &[&str]) -> Result<bool, RModError> {
+            match self.call(cmdname, args)?.to_reply()? {
+                Reply::Integer(n) => Ok(n != 0),
+                _ => Err(error!("Invalid type of CallReply, not Integer")),
+            }
+        }
+
+        fn call_mpop(&self, cmdname: &str, keys: &[&str], direction: &str, count: usize) -> Result<Option<RedisCallReply>, RModError> {
+            let numkeys = keys.len().to_string();
+            let count = count.to_string();
+            let mut args: Vec<&str> = Vec::with_capacity(keys.len() + 4);
+            args.push(numkeys.as_str());
+            args.extend_from_slice(keys);
+            args.push(direction);
+            args.push("count");
+            args.push(count.as_str());
+
+            let reply = self.call_replicated(cmdname, args.as_slice())?;
+            match reply.check_type() {
+                raw::ReplyType::Nil => Ok(None),
+                raw::ReplyType::Array => Ok(Some(reply)),
+                _ => Err(error!("Invalid type of CallReply, not Array")),
+            }
+        }
+
+
+    /// Returns flags describing the context the command runs in, e.g. whether
+    /// the instance is a replica (`SLAVE`) or the command runs inside a
+    /// transaction (`MULTI`) or a script (`LUA`). Flags only known to newer
+    /// servers are never set by older ones.
+    pub fn context_flags(&self) -> raw::ContextFlags {
+        raw::ContextFlags::from_bits_truncate(raw::get_context_flags(self.ctx))
+    }
+
+    /// Returns the current UNIX time in milliseconds. This is read from the
+    /// clock on every call, so it can differ within a command, and replicas
+    /// replaying the command read their own clock rather than the master's.
+    pub fn now_ms(&self) -> i64 {
+        raw::milliseconds()
+    }
+
+    /// Returns a monotonic time in microseconds, for measuring durations
+    /// that mustn't go backwards with the wall clock. Requires Redis 7 or
+    /// later.
+    pub fn monotonic_micros(&self) -> Result<u64, RModError> {
+        match raw::monotonic_microseconds() {
+            Some(micros) => Ok(micros),
+            None => Err(error!("Monotonic time requires Redis 7 or later")),
+        }
+    }
+
+    /// Returns the id of the client running the command, as in `CLIENT ID`.
+    /// This is 0 when there's no client, e.g. for a thread safe context.
+    pub fn client_id(&self) -> u64 {
+        raw::get_client_id(self.ctx)
+    }
+
+    /// Returns the address, port, database and name of the client running the
+    /// command. Requires Redis 6 or later.
+    pub fn client_info(&self) -> Result<ClientInfo, RModError> {
+        let id = self.client_id();
+        let mut info = raw::RedisModuleClientInfo {
+            version: raw::REDISMODULE_CLIENTINFO_VERSION,
+            flags:   0,
+            id:      0,
+            addr:    [0; 46],
+            port:    0,
+            db:      0,
+        };
+        match raw::get_client_info_by_id(&mut info, id) {
+            Some(raw::Status::Ok) => (),
+            Some(raw::Status::Err) => return Err(error!("Could not get info of client {}", id)),
+            None => return Err(error!("Client info requires Redis 6 or later")),
+        }
+
+        let addr = unsafe { CStr::from_ptr(info.addr.as_ptr()) };
+        let name = match raw::get_client_name_by_id(self.ctx, id) {
+            Some(name) if !name.is_null() => {
+                let val = manifest_redis_string(name);
+                raw::free_string(self.ctx, name);
+                Some(val?)
+            }
+            _ => None,
+        };
+        Ok(ClientInfo {
+            id: info.id,
+            addr: addr.to_string_lossy().into_owned(),
+            port: info.port,
+            db: info.db,
+            name,
+        })
+    }
+
+    /// Returns the id of the database the command operates on.
+    pub fn get_selected_db(&self) -> i32 {
+        raw::get_selected_db(self.ctx)
+    }
+
+    /// Switches the database that keys are opened in and commands are called
+    /// on. Errors for an id out of range, in which case Redis leaves the
+    /// selected database as it was.
+    ///
+    /// This also switches the database of the calling client, which keeps it
+    /// after the command returns. Commands that only visit another database
+    /// should switch back to the one from `get_selected_db` before returning.
+    pub fn select_db(&self, db: i32) -> Result<(), RModError> {
+        if raw::select_db(self.ctx, db) == raw::Status::Err {
+            return Err(error!("Could not select database {}, out of range", db));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of keys in the selected database, like `DBSIZE`.
+    /// On servers before Redis 6, this calls `DBSIZE` instead.
+    pub fn db_size(&self) -> Result<i64, RModError> {
+        match raw::db_size(self.ctx) {
+            Some(size) => Ok(size as i64),
+            None => self.call("dbsize", &[])?.to_integer(),
+        }
+    }
+
+    /// Returns the largest string a client may create, as configured with
+    /// `proto-max-bulk-len`. This calls `CONFIG GET`, so read it once (e.g.
+    /// while loading) rather than on every command.
+    pub fn proto_max_bulk_len(&self) -> Result<usize, RModError> {
+        let reply = self.call("config", &["get", "proto-max-bulk-len"])?;
+        Ok(reply.reply_array_element(1)?.to_string()?.parse::<usize>()?)
+    }
+
+    /// Returns the name of a random key in the selected database, or `None`
+    /// when it's empty. Requires Redis 6 or later.
+    pub fn random_key(&self) -> Result<Option<String>, RModError> {
+        match raw::random_key(self.ctx) {
+            Some(key) if key.is_null() => Ok(None),
+            Some(key) => {
+                let key = RedisString { ctx: self.ctx, str_inner: key };
+                Ok(Some(manifest_redis_string(key.str_inner)?))
+            }
+            None => Err(error!("Random keys require Redis 6 or later")),
+        }
+    }
+
+    /// Returns the name of the running command as it was invoked, including
+    /// the container command for a subcommand (e.g. `mymod|sub`). On servers
+    /// before Redis 6.2.5, this is `argv[0]` as given by the client instead.
+    pub fn current_command_name(&self) -> String {
+        if let Some(name) = raw::get_current_command_name(self.ctx) {
+            if !name.is_null() {
+                return unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned();
+            }
+        }
+        CURRENT_COMMAND.with(|name| name.borrow().clone()).unwrap_or_default()
+    }
 
     /// Coerces a Redis string as an integer.size_t///
     /// Redis is pretty dumb about data types. It nominally supports strings
@@ -210,7 +819,7 @@ impl Redis {
     pub fn log(&self, level: LogLevel, message: &str) {
         raw::log(
             self.ctx,
-            format!("{:?}\0", level).to_lowercase().as_ptr(),
+            level.as_c_str().as_ptr(),
             format!("{}\0", message).as_ptr(),
         );
     }
@@ -223,6 +832,51 @@ impl Redis {
         self.log(LogLevel::Notice, message);
     }
 
+    /// Errors with the canonical `LOADING` error while Redis is loading its
+    /// dataset from an RDB or AOF file.
+    ///
+    /// Commands not flagged `allow-loading` are already rejected by Redis, so
+    /// this is for commands that are, but still can't run during a load. Call
+    /// it at the top of `run`.
+    pub fn deny_during_loading(&self) -> Result<(), RModError> {
+        if self.context_flags().contains(raw::ContextFlags::LOADING) {
+            return Err(RModError::with_code("LOADING", "Redis is loading the dataset in memory"));
+        }
+        Ok(())
+    }
+
+    /// Errors with the canonical `CROSSSLOT` error if `keys` don't all hash
+    /// to the same cluster slot. Always succeeds outside of cluster mode.
+    ///
+    /// Multi-key commands should call it before touching any of their keys,
+    /// since Redis only checks the keys given by the command's key spec.
+    pub fn assert_same_slot(&self, keys: &[&str]) -> Result<(), RModError> {
+        if !self.context_flags().contains(raw::ContextFlags::CLUSTER) {
+            return Ok(());
+        }
+
+        let mut slots = keys.iter().map(|key| self.key_slot(key));
+        if let Some(first) = slots.next() {
+            if slots.any(|slot| slot != first) {
+                return Err(RModError::with_code(
+                    "CROSSSLOT",
+                    "Keys in request don't hash to the same slot",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Computes the cluster slot of a key, natively where the server supports
+    // it and the same way Redis does (CRC16 of the hash tag) otherwise.
+    fn key_slot(&self, key: &str) -> u32 {
+        let key_str = self.create_string(key);
+        match raw::cluster_key_slot(key_str.str_inner) {
+            Some(slot) => slot,
+            None => u32::from(crc16(hash_tag(key.as_bytes())) & 0x3FFF),
+        }
+    }
+
     /// Opens a Redis key for read access.
     pub fn open_key(&self, key: &str) -> RedisKey {
         RedisKey::open(self.ctx, key)
@@ -233,24 +887,146 @@ impl Redis {
         RedisKeyWritable::open(self.ctx, key)
     }
 
+    /// Opens a Redis key with explicit flags, e.g. `NOTOUCH` to read a key
+    /// without updating its LRU/LFU (so `OBJECT IDLETIME` isn't reset), or
+    /// `NONOTIFY` to open a missing key without a keyspace miss event.
+    ///
+    /// `READ` is always implied. Unless `WRITE` is given too, writes to the
+    /// returned key fail.
+    pub fn open_key_with_flags(&self, key: &str, flags: OpenKeyFlags) -> RedisKeyWritable {
+        RedisKeyWritable::open_with_mode(self.ctx, key, flags | raw::KeyMode::READ)
+    }
+
     /// Tells Redis that we're about to reply with an (Redis) array.
     /// Used by invoking once with the expected length and then calling any
     /// combination of the other reply_* methods exactly that number of times.
     pub fn reply_array(&self, len: i64) -> Result<(), RModError> {
+        self.mark_replied();
         handle_status(
             raw::reply_with_array(self.ctx, len as c_long),
             "Could not reply with long",
         )
     }
 
+    /// Starts a map reply of `len` key/value pairs, each of which must then
+    /// be replied as a key followed by its value.
+    ///
+    /// Clients speaking RESP2 get a flat array of `2 * len` elements instead,
+    /// the same as `HGETALL` replies with.
+    pub fn reply_map(&self, len: i64) -> Result<(), RModError> {
+        self.mark_replied();
+        if self.is_resp3() {
+            if let Some(status) = raw::reply_with_map(self.ctx, len as c_long) {
+                return handle_status(status, "Could not reply with map");
+            }
+        }
+        self.reply_array(len * 2)
+    }
+
+    /// Starts a set reply of `len` elements, for collections where the order
+    /// of elements is meaningless. Clients speaking RESP2 get an array.
+    pub fn reply_set(&self, len: i64) -> Result<(), RModError> {
+        self.mark_replied();
+        if self.is_resp3() {
+            if let Some(status) = raw::reply_with_set(self.ctx, len as c_long) {
+                return handle_status(status, "Could not reply with set");
+            }
+        }
+        self.reply_array(len)
+    }
+
+    /// Replies with a boolean. Clients speaking RESP2 get `1` or `0`.
+    pub fn reply_bool(&self, b: bool) -> Result<(), RModError> {
+        self.mark_replied();
+        if self.is_resp3() {
+            if let Some(status) = raw::reply_with_bool(self.ctx, b as c_int) {
+                return handle_status(status, "Could not reply with bool");
+            }
+        }
+        self.reply_integer(b as i64)
+    }
+
+    /// Replies with an integer too wide for `reply_integer`. Values that fit
+    /// in an `i64` are sent as plain integers, and wider ones as a big
+    /// number, or as a bulk string holding the decimal digits for clients
+    /// speaking RESP2.
+    pub fn reply_i128(&self, v: i128) -> Result<(), RModError> {
+        if let Ok(n) = i64::try_from(v) {
+            return self.reply_integer(n);
+        }
+        self.reply_big_number(&v.to_string())
+    }
+
+    /// Replies with an arbitrarily large integer given as its base 10
+    /// `digits`, optionally preceded by `-`. Clients speaking RESP2 (or
+    /// servers before Redis 7) get a bulk string holding the digits.
+    pub fn reply_big_number(&self, digits: &str) -> Result<(), RModError> {
+        let unsigned = digits.strip_prefix('-').unwrap_or(digits);
+        if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error!("Big number must be a base 10 integer, got {:?}", digits));
+        }
+        self.mark_replied();
+        if self.is_resp3() {
+            let status = raw::reply_with_big_number(self.ctx, digits.as_ptr() as *const c_char, digits.len());
+            if let Some(status) = status {
+                return handle_status(status, "Could not reply with big number");
+            }
+        }
+        self.reply_string(digits)
+    }
+
+    /// Replies with `data` as a verbatim string tagged with `format`, a
+    /// three letter hint like `txt` or `mkd` telling clients how to show it.
+    /// Clients speaking RESP2 (or servers before Redis 7) get a plain bulk
+    /// string without the format.
+    pub fn reply_verbatim_string(&self, format: &str, data: &[u8]) -> Result<(), RModError> {
+        if format.len() != 3 {
+            return Err(error!("Verbatim string format must be 3 bytes long, got {:?}", format));
+        }
+        let ext = CString::new(format)?;
+        self.mark_replied();
+        if self.is_resp3() {
+            let status = raw::reply_with_verbatim_string_type(
+                self.ctx,
+                data.as_ptr() as *const c_char,
+                data.len(),
+                ext.as_ptr(),
+            );
+            if let Some(status) = status {
+                return handle_status(status, "Could not reply with verbatim string");
+            }
+        }
+        handle_status(
+            raw::reply_with_string_buffer(self.ctx, data.as_ptr() as *const c_char, data.len()),
+            "Could not reply with string",
+        )
+    }
+
+    // Detects whether the client of the current command speaks RESP3.
+    fn is_resp3(&self) -> bool {
+        self.context_flags().contains(raw::ContextFlags::RESP3)
+    }
+
     pub fn reply_integer(&self, integer: i64) -> Result<(), RModError> {
+        self.mark_replied();
         handle_status(
             raw::reply_with_long_long(self.ctx, integer as c_longlong),
             "Could not reply with longlong",
         )
     }
 
+    /// Replies with a double. Clients speaking RESP2 get it as a bulk
+    /// string.
+    pub fn reply_double(&self, d: f64) -> Result<(), RModError> {
+        self.mark_replied();
+        handle_status(
+            raw::reply_with_double(self.ctx, d),
+            "Could not reply with double",
+        )
+    }
+
     pub fn reply_string(&self, message: &str) -> Result<(), RModError> {
+        self.mark_replied();
         let redis_str = self.create_string(message);
         handle_status(
             raw::reply_with_string(self.ctx, redis_str.str_inner),
@@ -258,7 +1034,32 @@ impl Redis {
         )
     }
 
+    /// Forwards the reply of a `call` to the client verbatim, including any
+    /// nested elements and error replies, without decoding it first.
+    pub fn reply_with_call_reply(&self, reply: &RedisCallReply) -> Result<(), RModError> {
+        self.mark_replied();
+        handle_status(
+            raw::reply_with_call_reply(self.ctx, reply.reply),
+            "Could not reply with call reply",
+        )
+    }
+
+    /// Replies with an error as `CODE message`, where `code` is a Redis error
+    /// code like `ERR` or `WRONGTYPE`. Errors without replying if `code`
+    /// isn't a single uppercase word, which clients wouldn't read as a code.
+    pub fn reply_error(&self, code: &str, message: &str) -> Result<(), RModError> {
+        if !is_error_code(code) {
+            return Err(error!("Invalid error code: {:?}", code));
+        }
+        self.mark_replied();
+        handle_status(
+            raw::reply_with_error(self.ctx, format!("{} {}\0", code, message).as_ptr()),
+            "Could not reply with error",
+        )
+    }
+
     pub fn reply_with_simple_string(&self, message: &str) {
+        self.mark_replied();
         raw::reply_with_simple_string(
             self.ctx,
             format!("{}\0",message).as_ptr()
@@ -266,20 +1067,141 @@ impl Redis {
     }
 
     pub fn reply_ok(&self){
+        self.mark_replied();
         raw::reply_with_simple_string(
             self.ctx,
             format!("OK\0").as_ptr()
         )
     }
 
+    /// Replies with a `Reply`, including nested arrays and maps. Errors for
+    /// the kinds of reply that carry nothing to send back, anywhere in the
+    /// tree, before anything is replied.
+    pub fn reply(&self, reply: &Reply) -> Result<(), RModError> {
+        check_replyable(reply)?;
+        self.write_reply(reply)
+    }
+
+    // Replies with a `Reply` already checked by `check_replyable`, so that a
+    // nested element can't fail after its array was started.
+    fn write_reply(&self, reply: &Reply) -> Result<(), RModError> {
+        match *reply {
+            Reply::Integer(n) => self.reply_integer(n),
+            Reply::String(ref s) => self.reply_string(s),
+            Reply::Double(d) => self.reply_double(d),
+            Reply::Bool(b) => self.reply_bool(b),
+            Reply::Nil => {
+                self.reply_null();
+                Ok(())
+            }
+            Reply::Array(ref elements) => {
+                self.reply_array(elements.len() as i64)?;
+                for element in elements {
+                    self.write_reply(element)?;
+                }
+                Ok(())
+            }
+            // Under RESP2 the map header becomes an array header twice as
+            // long, so the pairs below come out as a flat array.
+            Reply::Map(ref pairs) => {
+                self.reply_map(pairs.len() as i64)?;
+                for (key, value) in pairs {
+                    self.write_reply(key)?;
+                    self.write_reply(value)?;
+                }
+                Ok(())
+            }
+            // Already ruled out by `check_replyable`.
+            Reply::Error | Reply::Unknown => {
+                Err(error!("Could not reply with {:?}, no value to reply with", reply))
+            }
+        }
+    }
+
+    /// Replies `OK` on success and with the error otherwise, using its code
+    /// if it carries one. The result has been replied either way, so the
+    /// returned value is always `Ok` and can be handed straight back from
+    /// `Command::run`.
+    pub fn reply_result(&self, res: Result<(), RModError>) -> Result<(), RModError> {
+        self.mark_replied();
+        match res {
+            Ok(()) => self.reply_ok(),
+            Err(e) => {
+                reply_with_rmod_error(self.ctx, &e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replies with a null bulk string (`$-1` under RESP2, `_` under RESP3).
+    ///
+    /// This is the nil that `GET` returns for a missing key, so use it when
+    /// the command would otherwise reply with a single string value.
     pub fn reply_null(&self) {
+        self.mark_replied();
         raw::reply_with_null(self.ctx);
     }
 
+    /// Replies with a null array (`*-1` under RESP2, `_` under RESP3).
+    ///
+    /// This is the nil that `BLPOP` returns on timeout, so use it when the
+    /// command would otherwise reply with an array. Some older RESP2 clients
+    /// parse a null bulk string and a null array differently, so returning
+    /// the wrong shape can break them in subtle ways. Requires Redis 6 or
+    /// later.
+    pub fn reply_nil_array(&self) -> Result<(), RModError> {
+        let status = raw::reply_with_null_array(self.ctx)
+            .ok_or_else(|| error!("Null array replies require Redis 6 or later"))?;
+        self.mark_replied();
+        handle_status(status, "Could not reply with null array")
+    }
+
     pub fn replicate_verbatim(&self) {
         raw::replicate_verbatim(self.ctx);
     }
 
+    /// Propagates `cmd` with `args` to the AOF and replicas in place of the
+    /// command being run, e.g. to replicate a canonical form of what it did.
+    /// Can be called several times, and the commands are propagated in order
+    /// wrapped in `MULTI`/`EXEC` once the command returns.
+    pub fn replicate(&self, cmd: &str, args: &[&str]) -> Result<(), RModError> {
+        let cmdname = CString::new(cmd)?;
+        let redis_args: Vec<RedisString> = args.iter().map(|a| self.create_string(a)).collect();
+        let mut argv: Vec<*mut raw::RedisModuleString> = redis_args.iter().map(|a| a.str_inner).collect();
+        // `cmdname` and `argv` both outlive the call.
+        let status = unsafe { raw::replicate_argv(self.ctx, cmdname.as_ptr(), argv.as_mut_ptr(), argv.len()) };
+        handle_status(status, "Could not replicate command, unknown command or wrong arity")
+    }
+
+    /// Fires a keyspace notification for `key`, as native commands do after
+    /// modifying a key, so that clients subscribed to `__keyspace@<db>__:`
+    /// or `__keyevent@<db>__:` channels see writes done through the module
+    /// API too. `types` tells which `notify-keyspace-events` classes the
+    /// event belongs to, and the event is only published if one of them is
+    /// enabled.
+    pub fn notify_keyspace_event(&self, types: raw::NotifyFlags, event: &str, key: &str) -> Result<(), RModError> {
+        let event = CString::new(event)?;
+        let key_str = self.create_string(key);
+        match raw::notify_keyspace_event(self.ctx, types, event.as_ptr(), key_str.str_inner) {
+            Some(status) => handle_status(status, "Could not notify keyspace event"),
+            None => Err(error!("Keyspace notifications require a newer Redis")),
+        }
+    }
+
+}
+
+/// What Redis knows about a connected client, as returned by
+/// `Redis::client_info`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientInfo {
+    pub id:   u64,
+    pub addr: String,
+    pub port: u16,
+    pub db:   u16,
+
+    /// The name set with `CLIENT SETNAME`, if any. Always `None` on servers
+    /// before Redis 7.
+    pub name: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -288,6 +1210,45 @@ pub enum KeyMode {
     ReadWrite,
 }
 
+/// `KeyType` is the kind of value held by an opened Redis key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyType {
+    Empty,
+    String,
+    List,
+    Hash,
+    Set,
+    Zset,
+    Module,
+    Stream,
+}
+
+/// `BitfieldType` is the width and signedness of an integer packed into a
+/// string value, as used by `BITFIELD` (e.g. `i8`, `u16`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitfieldType {
+    Signed(u8),
+    Unsigned(u8),
+}
+
+impl fmt::Display for BitfieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitfieldType::Signed(bits) => write!(f, "i{}", bits),
+            BitfieldType::Unsigned(bits) => write!(f, "u{}", bits),
+        }
+    }
+}
+
+/// `BitfieldOp` is a single operation of a `BITFIELD` command. `offset` is
+/// counted in bits from the start of the string.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitfieldOp {
+    Get { ty: BitfieldType, offset: u64 },
+    Set { ty: BitfieldType, offset: u64, value: i64 },
+    IncrBy { ty: BitfieldType, offset: u64, increment: i64 },
+}
+
 /// `RedisKey` is an abstraction over a Redis key that allows readonly
 /// operations.
 ///
@@ -314,101 +1275,649 @@ impl RedisKey {
         }
     }
 
-    /// Detects whether the key pointer given to us by Redis is null.
-    pub fn is_null(&self) -> bool {
-        let null_key: *mut raw::RedisModuleKey = ptr::null_mut();
-        self.key_inner == null_key
+    /// Detects whether the key pointer given to us by Redis is null.
+    pub fn is_null(&self) -> bool {
+        let null_key: *mut raw::RedisModuleKey = ptr::null_mut();
+        self.key_inner == null_key
+    }
+
+    /// Returns the kind of value held by the key, or `KeyType::Empty` if the
+    /// key doesn't exist.
+    pub fn key_type(&self) -> KeyType {
+        from_raw_key_type(raw::key_type(self.key_inner))
+    }
+
+    /// Detects whether the key exists. Unlike `read`, this tells a missing
+    /// key apart from one holding an empty string, and doesn't read the value.
+    pub fn exists(&self) -> bool {
+        raw::key_type(self.key_inner) != raw::KeyType::Empty
+    }
+
+    /// Reads the string value of the key, or `None` if the key doesn't
+    /// exist. Errors with `WRONGTYPE` when the key holds another kind of
+    /// value.
+    pub fn read(&self) -> Result<Option<String>, RModError> {
+        match self.read_bytes()? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Same as `read`, but returns the bytes of the value as they are, for
+    /// binary values that aren't valid UTF-8.
+    pub fn read_bytes(&self) -> Result<Option<Vec<u8>>, RModError> {
+        let val = if self.is_null() {
+            None
+        } else {
+            check_string_key(self.key_inner)?;
+            Some(read_key_bytes(self.key_inner))
+        };
+        Ok(val)
+    }
+
+    /// Returns the number of seconds since the key was last accessed, like
+    /// `OBJECT IDLETIME`. Errors when an LFU `maxmemory-policy` is in use,
+    /// since Redis doesn't track idle time then.
+    ///
+    /// Note that opening the key counts as an access unless it's opened with
+    /// `NOTOUCH` (see `Redis::open_key_with_flags`).
+    pub fn idle_time(&self) -> Result<u64, RModError> {
+        if self.is_null() {
+            return Err(error!("Error while reading idletime of key, no such key"));
+        }
+        let mut idle_ms: c_longlong = 0;
+        match raw::get_lru(self.key_inner, &mut idle_ms) {
+            Some(raw::Status::Ok) => Ok(idle_ms as u64 / 1000),
+            Some(raw::Status::Err) => Err(error!(
+                "Error while reading idletime of key, not supported by the current maxmemory-policy"
+            )),
+            None => self.object_metric("idletime"),
+        }
+    }
+
+    /// Returns the logarithmic access frequency counter of the key, like
+    /// `OBJECT FREQ`. Errors unless an LFU `maxmemory-policy` is in use.
+    pub fn access_freq(&self) -> Result<u64, RModError> {
+        if self.is_null() {
+            return Err(error!("Error while reading freq of key, no such key"));
+        }
+        let mut freq: c_longlong = 0;
+        match raw::get_lfu(self.key_inner, &mut freq) {
+            Some(raw::Status::Ok) => Ok(freq as u64),
+            Some(raw::Status::Err) => Err(error!(
+                "Error while reading freq of key, not supported by the current maxmemory-policy"
+            )),
+            None => self.object_metric("freq"),
+        }
+    }
+
+    #[deprecated(note = "renamed to `access_freq`")]
+    pub fn freq(&self) -> Result<u64, RModError> {
+        self.access_freq()
+    }
+
+    // Reads a metric of the key through `OBJECT`, for servers before Redis 6
+    // where the module API doesn't expose it.
+    fn object_metric(&self, subcommand: &str) -> Result<u64, RModError> {
+        let key = manifest_redis_string(self.key_str.str_inner)?;
+        let reply = Redis::new(self.ctx).call("object", &[subcommand, key.as_str()])?;
+        match reply.check_type() {
+            raw::ReplyType::Integer => Ok(reply.to_integer()? as u64),
+            raw::ReplyType::Nil => Err(error!("Error while reading {} of key, no such key", subcommand)),
+            _ => Err(error!(
+                "Error while reading {} of key, not supported by the current maxmemory-policy",
+                subcommand
+            )),
+        }
+    }
+
+    /// Detects whether both handles were opened for the same key name, so a
+    /// command can reject aliased arguments (e.g. `COPY key key`) before
+    /// operating on them.
+    pub fn same_key_as(&self, other: &RedisKey) -> bool {
+        raw::string_compare(self.key_str.str_inner, other.key_str.str_inner) == 0
+    }
+
+}
+
+
+impl Drop for RedisKey {
+// Frees resources appropriately as a RedisKey goes out of scope.
+    fn drop(&mut self) {
+        raw::close_key(self.key_inner);
+    }
+}
+
+/// `RedisKeyWritable` is an abstraction over a Redis key that allows read and
+/// write operations.
+pub struct RedisKeyWritable {
+    ctx:       *mut raw::RedisModuleCtx,
+    key_inner: *mut raw::RedisModuleKey,
+
+    // The Redis string
+    //
+    // This field is needed on the struct so that its Drop implementation gets
+    // called when it goes out of scope.
+    #[allow(dead_code)]
+    key_str: RedisString,
+}
+
+
+impl RedisKeyWritable {
+    fn open(ctx: *mut raw::RedisModuleCtx, key: &str) -> RedisKeyWritable {
+        RedisKeyWritable::open_with_mode(ctx, key, to_raw_mode(KeyMode::ReadWrite))
+    }
+
+    fn open_with_mode(ctx: *mut raw::RedisModuleCtx, key: &str, mode: raw::KeyMode) -> RedisKeyWritable {
+        let key_str = RedisString::create(ctx, key);
+        let key_inner = raw::open_key(ctx, key_str.str_inner, mode);
+        RedisKeyWritable {
+            ctx,
+            key_inner,
+            key_str,
+        }
+    }
+
+    /// Detects whether the value stored in a Redis key is empty.
+    ///
+    /// Note that an empty key can be reliably detected by looking for a null
+    /// as you open the key in read mode, but when asking for write Redis
+    /// returns a non-null pointer to allow us to write to even an empty key,
+    /// so we have to check the key's value instead.
+    pub fn is_empty(&self) -> Result<bool, RModError> {
+        match self.read()? {
+            Some(s) => match s.as_str() {
+                "" => Ok(true),
+                _ => Ok(false),
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Reads the string value of the key. Errors with `WRONGTYPE` when the
+    /// key holds another kind of value.
+    pub fn read(&self) -> Result<Option<String>, RModError> {
+        check_string_key(self.key_inner)?;
+        Ok(Some(read_key(self.key_inner)?))
+    }
+
+    /// Returns the kind of value held by the key, or `KeyType::Empty` if the
+    /// key doesn't exist.
+    pub fn key_type(&self) -> KeyType {
+        from_raw_key_type(raw::key_type(self.key_inner))
+    }
+
+    pub fn set_expire(&self, expire: time::Duration) -> Result<(), RModError> {
+        match raw::set_expire(self.key_inner, expire.num_milliseconds()) {
+            raw::Status::Ok => Ok(()),
+
+            // Error may occur if the key wasn't open for writing or is an
+            // empty key.
+            raw::Status::Err => Err(error!("Error while setting key expire")),
+        }
+    }
+
+    /// Clears the expiry of the key, like `PERSIST`.
+    pub fn persist(&self) -> Result<(), RModError> {
+        match raw::set_expire(self.key_inner, raw::REDISMODULE_NO_EXPIRE) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while clearing key expire")),
+        }
+    }
+
+    /// Returns the remaining time to live of the key, or `None` if the key
+    /// has no expiry (or doesn't exist).
+    pub fn get_expire(&self) -> Result<Option<time::Duration>, RModError> {
+        match raw::get_expire(self.key_inner) {
+            raw::REDISMODULE_NO_EXPIRE => Ok(None),
+            ttl if ttl < 0 => Err(error!("Error while getting key expire")),
+            ttl => Ok(Some(time::Duration::milliseconds(ttl))),
+        }
+    }
+
+    /// Reads the string value of the key and updates its expiry in the same
+    /// step, like `GETEX`. `None` clears the expiry as with `PERSIST`, while
+    /// `Some` sets it as with `PX`. A missing key is left untouched. Like
+    /// any write through an open key, the expiry change isn't replicated.
+    pub fn get_ex(&self, ttl: Option<time::Duration>) -> Result<Option<String>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(None),
+            raw::KeyType::String => (),
+            _ => return Err(error!("Error while getex to key, not String structure")),
+        }
+
+        let val = read_key(self.key_inner)?;
+        let expire = match ttl {
+            Some(ttl) => ttl.num_milliseconds(),
+            None => raw::REDISMODULE_NO_EXPIRE,
+        };
+        match raw::set_expire(self.key_inner, expire) {
+            raw::Status::Ok => Ok(Some(val)),
+            raw::Status::Err => Err(error!("Error while setting key expire")),
+        }
+    }
+
+    /// Returns the size of the value held by the key: the number of elements
+    /// of a list, set, hash, sorted set or stream (like `XLEN`), or the
+    /// length of a string. A missing key has a size of 0.
+    pub fn cardinality(&self) -> Result<usize, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => Ok(0),
+            raw::KeyType::String
+            | raw::KeyType::List
+            | raw::KeyType::Hash
+            | raw::KeyType::Set
+            | raw::KeyType::Zset
+            | raw::KeyType::Stream => Ok(raw::value_length(self.key_inner)),
+            _ => Err(error!("Error while reading cardinality of key, unsupported structure")),
+        }
+    }
+
+    /// Returns the length in bytes of the string value, like `STRLEN`,
+    /// without copying the value. A missing key has a length of 0.
+    pub fn strlen(&self) -> Result<usize, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(0),
+            raw::KeyType::String => (),
+            _ => return Err(error!("Error while strlen to key, not String structure")),
+        }
+
+        let mut length: size_t = 0;
+        raw::string_dma(self.key_inner, &mut length, raw::KeyMode::READ);
+        Ok(length)
+    }
+
+    /// Returns the part of the string value between `start` and `end`, both
+    /// inclusive, like `GETRANGE`. Negative offsets count back from the end
+    /// of the string, and out of range offsets are clamped to it, so that a
+    /// missing key or an empty range gives an empty string.
+    pub fn get_range(&self, start: i64, end: i64) -> Result<String, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(String::new()),
+            raw::KeyType::String => (),
+            _ => return Err(error!("Error while getrange to key, not String structure")),
+        }
+
+        let mut length: size_t = 0;
+        let dma = raw::string_dma(self.key_inner, &mut length, raw::KeyMode::READ);
+        let len = length as i64;
+        if start < 0 && end < 0 && start > end {
+            return Ok(String::new());
+        }
+        let start = if start < 0 { (len + start).max(0) } else { start };
+        let end = if end < 0 { (len + end).max(0) } else { end.min(len - 1) };
+        if len == 0 || start > end {
+            return Ok(String::new());
+        }
+
+        let range = unsafe { dma.offset(start as isize) };
+        Ok(from_byte_string(range, (end - start + 1) as size_t)?)
+    }
+
+    /// Overwrites part of the string value starting at `offset`, like
+    /// `SETRANGE`, and returns the length of the string afterwards.
+    ///
+    /// The string is zero-padded if `offset` is past its end, and a missing
+    /// key is created as a zero-padded string. Errors if the string would
+    /// grow past `max_len`, which commands behaving like `SETRANGE` take from
+    /// `Redis::proto_max_bulk_len`.
+    pub fn set_range(&self, offset: usize, data: &[u8], max_len: usize) -> Result<usize, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty | raw::KeyType::String => (),
+            _ => return Err(error!("Error while setrange to key, not String structure")),
+        }
+
+        let mut length: size_t = 0;
+        raw::string_dma(self.key_inner, &mut length, raw::KeyMode::READ);
+        if data.is_empty() {
+            return Ok(length);
+        }
+
+        let too_large = || RModError::with_code("ERR", "string exceeds maximum allowed size (proto-max-bulk-len)");
+        let new_length = match offset.checked_add(data.len()) {
+            Some(end) => length.max(end),
+            None => return Err(too_large()),
+        };
+        if new_length > length {
+            if new_length > max_len {
+                return Err(too_large());
+            }
+            if let raw::Status::Err = raw::string_truncate(self.key_inner, new_length) {
+                return Err(error!("Error while setrange to key, string exceeds maximum allowed size"));
+            }
+        }
+
+        let dma = raw::string_dma(
+            self.key_inner,
+            &mut length,
+            raw::KeyMode::READ | raw::KeyMode::WRITE,
+        ) as *mut u8;
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), dma.add(offset), data.len()) };
+        Ok(new_length)
+    }
+
+    /// Resizes the string value to `new_len` bytes in place, cutting it
+    /// short or padding it with zero bytes. A missing key is created as a
+    /// zero-filled string, unless `new_len` is 0.
+    pub fn truncate(&self, new_len: usize) -> Result<(), RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty | raw::KeyType::String => (),
+            _ => return Err(error!("Error while truncate to key, not String structure")),
+        }
+
+        match raw::string_truncate(self.key_inner, new_len) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while truncate to key, string exceeds maximum allowed size")),
+        }
+    }
+
+    pub fn write(&self, val: &str) -> Result<(), RModError> {
+        let val_str = RedisString::create(self.ctx, val);
+        match raw::string_set(self.key_inner, val_str.str_inner) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while setting key")),
+        }
+    }
+
+    /// Replaces the string value of the key with `new_val` and returns the
+    /// previous one, like `GETSET`, or `None` if the key didn't exist. As
+    /// with `GETSET`, the expiry of the key is cleared.
+    pub fn get_set(&self, new_val: &str) -> Result<Option<String>, RModError> {
+        let old = match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => None,
+            raw::KeyType::String => Some(read_key(self.key_inner)?),
+            _ => return Err(error!("Error while getset to key, not String structure")),
+        };
+        self.write(new_val)?;
+        Ok(old)
+    }
+
+    /// Adds `delta` to the integer held by the key, like `INCRBY`, and
+    /// returns the new value. A missing key is taken to hold 0. The expiry
+    /// of the key, if any, is kept.
+    pub fn incr_by(&self, delta: i64) -> Result<i64, RModError> {
+        let current = match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => Reply::Integer(0),
+            _ => {
+                let val = self.read()?.unwrap_or_default();
+                Redis::new(self.ctx).coerce_integer(Ok(Reply::String(val)))?
+            }
+        };
+        let current = match current {
+            Reply::Integer(n) => n,
+            _ => return Err(RModError::with_code("ERR", "value is not an integer or out of range")),
+        };
+        let new = match current.checked_add(delta) {
+            Some(new) => new,
+            None => return Err(RModError::with_code("ERR", "increment or decrement would overflow")),
+        };
+
+        let ttl = self.get_expire()?;
+        self.write(&new.to_string())?;
+        if let Some(ttl) = ttl {
+            self.set_expire(ttl)?;
+        }
+        Ok(new)
+    }
+
+    /// Deletes the key, like `DEL`.
+    pub fn delete(&self) -> Result<(), RModError> {
+        match raw::delete_key(self.key_inner){
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while deleting key"))
+        }
+    }
+
+    #[deprecated(note = "renamed to `delete`")]
+    pub fn erace(&self) -> Result<(), RModError> {
+        self.delete()
+    }
+
+    /// Deletes the key like `UNLINK`, which frees the value's memory in the
+    /// background. Prefer it over `delete` for potentially large values.
+    pub fn unlink(&self) -> Result<(), RModError> {
+        match raw::unlink_key(self.key_inner){
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while unlinking key"))
+        }
+    }
+
+    /// Serializes the value of the key like `DUMP`, in a form that can be
+    /// restored with `Redis::restore`, e.g. on another server. Errors if the
+    /// key doesn't exist.
+    pub fn dump(&self) -> Result<Vec<u8>, RModError> {
+        if raw::key_type(self.key_inner) == raw::KeyType::Empty {
+            return Err(error!("Error while dump to key, no such key"));
+        }
+        let key = manifest_redis_string(self.key_str.str_inner)?;
+        Redis::new(self.ctx).call("dump", &[key.as_str()])?.to_bytes()
+    }
+
+    /// Records an access to the key like `TOUCH`, for when a read through a
+    /// handle that doesn't count as one should still keep the key warm.
+    ///
+    /// What gets updated depends on the `maxmemory-policy`: under an LRU (or
+    /// non-evicting) policy the idle time is reset, and under an LFU policy
+    /// the access frequency counter is incremented. Does nothing for a
+    /// missing key.
+    pub fn touch(&self) -> Result<(), RModError> {
+        let key = manifest_redis_string(self.key_str.str_inner)?;
+        Redis::new(self.ctx).call("touch", &[key.as_str()])?;
+        Ok(())
     }
 
-    pub fn read(&self) -> Result<Option<String>, RModError> {
-        let val = if self.is_null() {
-            None
-        } else {
-            Some(read_key(self.key_inner)?)
-        };
-        Ok(val)
+    /// Adds `member` to the sorted set with `score`, or updates its score if
+    /// it's already a member, like `ZADD`. A missing key is created.
+    pub fn zadd(&self, score: f64, member: &str) -> Result<(), RModError> {
+        let member_str = RedisString::create(self.ctx, member);
+        let mut flags: c_int = 0;
+        match raw::zset_add(self.key_inner, score, member_str.str_inner, &mut flags) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(error!("Error while zadd to key, tried to the wrong type or score is NaN"))
+        }
     }
 
-}
+    /// Returns the score of `member` in the sorted set, or `None` if it's not
+    /// a member (or the key doesn't exist).
+    pub fn zscore(&self, member: &str) -> Result<Option<f64>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(None),
+            raw::KeyType::Zset => (),
+            _ => return Err(error!("Error while zscore to key, not Zset structure")),
+        }
 
+        let member_str = RedisString::create(self.ctx, member);
+        let mut score: f64 = 0.0;
+        match raw::zset_score(self.key_inner, member_str.str_inner, &mut score) {
+            raw::Status::Ok => Ok(Some(score)),
+            raw::Status::Err => Ok(None),
+        }
+    }
 
-impl Drop for RedisKey {
-// Frees resources appropriately as a RedisKey goes out of scope.
-    fn drop(&mut self) {
-        raw::close_key(self.key_inner);
+    /// Returns the members of the sorted set with ranks from `start` to
+    /// `stop` (both inclusive) along with their scores, in rank order, like
+    /// `ZRANGE ... WITHSCORES`. Negative ranks count back from the end.
+    pub fn zrange(&self, start: i64, stop: i64) -> Result<Vec<(String, f64)>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(Vec::new()),
+            raw::KeyType::Zset => (),
+            _ => return Err(error!("Error while zrange to key, not Zset structure")),
+        }
+
+        let len = raw::value_length(self.key_inner) as i64;
+        let start = if start < 0 { (len + start).max(0) } else { start };
+        let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+        if start > stop || start >= len {
+            return Ok(Vec::new());
+        }
+
+        // The module API can only start a range at a score, so walk from the
+        // lowest one and skip the members ranked before `start`.
+        let range = ZsetRange::first_in_score_range(self.key_inner, f64::NEG_INFINITY, f64::INFINITY)?;
+        let mut members = Vec::with_capacity((stop - start + 1) as usize);
+        let mut rank: i64 = 0;
+        while rank <= stop && !range.end_reached() {
+            if rank >= start {
+                members.push(range.current_element(self.ctx)?);
+            }
+            range.next();
+            rank += 1;
+        }
+        Ok(members)
     }
-}
 
-/// `RedisKeyWritable` is an abstraction over a Redis key that allows read and
-/// write operations.
-pub struct RedisKeyWritable {
-    ctx:       *mut raw::RedisModuleCtx,
-    key_inner: *mut raw::RedisModuleKey,
+    /// Returns the elements of a list between `start` and `stop`, both
+    /// inclusive, like `LRANGE`. Negative indexes count back from the tail,
+    /// and a missing key gives an empty vector. Requires Redis 7 or later.
+    pub fn lrange(&self, start: i64, stop: i64) -> Result<Vec<String>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(Vec::new()),
+            raw::KeyType::List => (),
+            _ => return Err(error!("Error while lrange to key, not List structure")),
+        }
 
-    // The Redis string
-    //
-    // This field is needed on the struct so that its Drop implementation gets
-    // called when it goes out of scope.
-    #[allow(dead_code)]
-    key_str: RedisString,
-}
+        let len = raw::value_length(self.key_inner) as i64;
+        let start = if start < 0 { (len + start).max(0) } else { start };
+        let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+        if start > stop || start >= len {
+            return Ok(Vec::new());
+        }
 
+        let mut elements = Vec::with_capacity((stop - start + 1) as usize);
+        for index in start..=stop {
+            match self.list_get(index)? {
+                Some(ele) => elements.push(ele),
+                None => break,
+            }
+        }
+        Ok(elements)
+    }
 
-impl RedisKeyWritable {
-    fn open(ctx: *mut raw::RedisModuleCtx, key: &str) -> RedisKeyWritable {
-        let key_str = RedisString::create(ctx, key);
-        let key_inner =
-            raw::open_key(ctx, key_str.str_inner, to_raw_mode(KeyMode::ReadWrite));
-        RedisKeyWritable {
-            ctx,
-            key_inner,
-            key_str,
+    /// Returns the list element at `index`, like `LINDEX`, or `None` if
+    /// it's out of range or the key doesn't exist. Negative indexes count
+    /// back from the tail. Requires Redis 7 or later.
+    pub fn lindex(&self, index: i64) -> Result<Option<String>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(None),
+            raw::KeyType::List => (),
+            _ => return Err(error!("Error while lindex to key, not List structure")),
         }
+
+        let len = raw::value_length(self.key_inner) as i64;
+        if index >= len || index < -len {
+            return Ok(None);
+        }
+        self.list_get(index)
     }
 
-    /// Detects whether the value stored in a Redis key is empty.
-    ///
-    /// Note that an empty key can be reliably detected by looking for a null
-    /// as you open the key in read mode, but when asking for write Redis
-    /// returns a non-null pointer to allow us to write to even an empty key,
-    /// so we have to check the key's value instead.
-    pub fn is_empty(&self) -> Result<bool, RModError> {
-        match self.read()? {
-            Some(s) => match s.as_str() {
-                "" => Ok(true),
-                _ => Ok(false),
-            },
-            _ => Ok(false),
+    /// Replaces the list element at `index`, like `LSET`. Negative indexes
+    /// count back from the tail. Errors if the index is out of range or the
+    /// key doesn't exist. Requires Redis 7 or later.
+    pub fn lset(&self, index: i64, value: &str) -> Result<(), RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Err(error!("Error while lset to key, no such key")),
+            raw::KeyType::List => (),
+            _ => return Err(error!("Error while lset to key, not List structure")),
+        }
+
+        let val_str = RedisString::create(self.ctx, value);
+        match raw::list_set(self.key_inner, index as c_long, val_str.str_inner) {
+            Some(raw::Status::Ok) => Ok(()),
+            Some(raw::Status::Err) => Err(error!("Error while lset to key, index out of range")),
+            None => Err(error!("List access by index requires Redis 7 or later")),
         }
     }
 
-    pub fn read(&self) -> Result<Option<String>, RModError> {
-        Ok(Some(read_key(self.key_inner)?))
+    /// Inserts `value` so that it ends up at `index` in the list: `0` inserts
+    /// at the head and `-1` at the tail. A missing key is created as a list.
+    /// Errors if the index is out of range. Requires Redis 7 or later.
+    pub fn linsert(&self, index: i64, value: &str) -> Result<(), RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty | raw::KeyType::List => (),
+            _ => return Err(error!("Error while linsert to key, not List structure")),
+        }
+
+        let val_str = RedisString::create(self.ctx, value);
+        match raw::list_insert(self.key_inner, index as c_long, val_str.str_inner) {
+            Some(raw::Status::Ok) => Ok(()),
+            Some(raw::Status::Err) => Err(error!("Error while linsert to key, index out of range")),
+            None => Err(error!("List access by index requires Redis 7 or later")),
+        }
     }
 
-    pub fn set_expire(&self, expire: time::Duration) -> Result<(), RModError> {
-        match raw::set_expire(self.key_inner, expire.num_milliseconds()) {
-            raw::Status::Ok => Ok(()),
+    /// Appends an entry made of `fields` to the stream, like `XADD key *`,
+    /// and returns the ID generated for it. A missing key is created as a
+    /// stream. Requires Redis 6.2 or later.
+    pub fn xadd(&self, fields: &[(&str, &str)]) -> Result<String, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty | raw::KeyType::Stream => (),
+            _ => return Err(error!("Error while xadd to key, not Stream structure")),
+        }
+        if fields.is_empty() {
+            return Err(error!("Error while xadd to key, no fields given"));
+        }
 
-            // Error may occur if the key wasn't open for writing or is an
-            // empty key.
-            raw::Status::Err => Err(error!("Error while setting key expire")),
+        let field_strs: Vec<RedisString> = fields
+            .iter()
+            .flat_map(|(field, val)| [RedisString::create(self.ctx, field), RedisString::create(self.ctx, val)])
+            .collect();
+        let mut argv: Vec<*mut raw::RedisModuleString> = field_strs.iter().map(|s| s.str_inner).collect();
+        let mut id = raw::RedisModuleStreamID::default();
+        match raw::stream_add(
+            self.key_inner,
+            raw::REDISMODULE_STREAM_ADD_AUTOID,
+            &mut id,
+            argv.as_mut_ptr(),
+            fields.len() as i64,
+        ) {
+            Some(raw::Status::Ok) => Ok(format!("{}-{}", id.ms, id.seq)),
+            Some(raw::Status::Err) => Err(error!("Error while xadd to key, could not add entry")),
+            None => Err(error!("Streams require Redis 6.2 or later")),
         }
     }
 
-    pub fn write(&self, val: &str) -> Result<(), RModError> {
-        let val_str = RedisString::create(self.ctx, val);
-        match raw::string_set(self.key_inner, val_str.str_inner) {
-            raw::Status::Ok => Ok(()),
-            raw::Status::Err => Err(error!("Error while setting key")),
+    /// Returns the entries of the stream with IDs between `start` and `end`,
+    /// both inclusive, like `XRANGE`: each entry ID along with its
+    /// field/value pairs. IDs are given as `ms-seq` or just `ms`, with `-`
+    /// and `+` standing for the lowest and highest possible IDs. Requires
+    /// Redis 6.2 or later.
+    pub fn xrange(&self, start: &str, end: &str) -> Result<Vec<StreamEntry>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(Vec::new()),
+            raw::KeyType::Stream => (),
+            _ => return Err(error!("Error while xrange to key, not Stream structure")),
+        }
+
+        let mut start_id = parse_stream_id(start, 0)?;
+        let mut end_id = parse_stream_id(end, u64::MAX)?;
+        if start_id > end_id {
+            return Ok(Vec::new());
+        }
+
+        let iter = StreamIterator::start(self.key_inner, &mut start_id, &mut end_id)?;
+        let mut entries = Vec::new();
+        while let Some((id, numfields)) = iter.next_id() {
+            let mut fields = Vec::with_capacity(numfields);
+            while let Some(pair) = iter.next_field(self.ctx)? {
+                fields.push(pair);
+            }
+            entries.push((format!("{}-{}", id.ms, id.seq), fields));
         }
+        Ok(entries)
     }
 
-    pub fn erace(&self) -> Result<(), RModError> {
-        match raw::delete_key(self.key_inner){
-            raw::Status::Ok => Ok(()),
-            raw::Status::Err => Err(error!("Error while eracing key"))
+    // Returns the list element at `index`, or `None` if it's out of range.
+    fn list_get(&self, index: i64) -> Result<Option<String>, RModError> {
+        let ele = match raw::list_get(self.key_inner, index as c_long) {
+            Some(ele) => ele,
+            None => return Err(error!("List access by index requires Redis 7 or later")),
+        };
+        if ele.is_null() {
+            return Ok(None);
         }
+        let val = manifest_redis_string(ele);
+        raw::free_string(self.ctx, ele);
+        Ok(Some(val?))
     }
 
     pub fn rpush(&self, ele: &str) -> Result<(), RModError> {
@@ -468,6 +1977,46 @@ impl RedisKeyWritable {
         }
     }
 
+    /// Reads several fields of a hash key at once, like `HMGET`. The values
+    /// come back in the order of `fields`, with `None` for missing fields.
+    pub fn hmget(&self, fields: &[&str]) -> Result<Vec<Option<String>>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(vec![None; fields.len()]),
+            raw::KeyType::Hash => (),
+            _ => return Err(error!("Error while hmget to key, not Hash structure")),
+        }
+
+        let fld_strs: Vec<RedisString> =
+            fields.iter().map(|f| RedisString::create(self.ctx, f)).collect();
+        let mut fld_ptrs: Vec<*mut raw::RedisModuleString> =
+            fld_strs.iter().map(|f| f.str_inner).collect();
+        let mut val_ptrs: Vec<*mut raw::RedisModuleString> = vec![ptr::null_mut(); fields.len()];
+        // Both vectors hold `fields.len()` elements.
+        unsafe {
+            raw::rm_hash_mget(
+                self.key_inner,
+                fld_ptrs.as_mut_ptr(),
+                val_ptrs.as_mut_ptr(),
+                fields.len()
+            );
+        }
+
+        // Take ownership of every value up front so that they're all freed
+        // even if one of them fails to decode.
+        let val_strs: Vec<Option<RedisString>> = val_ptrs
+            .into_iter()
+            .map(|v| if v.is_null() { None } else { Some(RedisString { ctx: self.ctx, str_inner: v }) })
+            .collect();
+        let mut vals: Vec<Option<String>> = Vec::with_capacity(fields.len());
+        for val_str in val_strs.iter() {
+            match val_str {
+                Some(val_str) => vals.push(Some(manifest_redis_string(val_str.str_inner)?)),
+                None => vals.push(None),
+            }
+        }
+        Ok(vals)
+    }
+
     pub fn rm_hset(&self, field: &str, val: &str) -> Result<(), RModError> {
         let fld_str = RedisString::create(self.ctx, field);
         let val_str = RedisString::create(self.ctx, val);
@@ -482,6 +2031,90 @@ impl RedisKeyWritable {
             ))
         }
     }
+
+    /// Runs several `BITFIELD` operations on the key atomically and returns
+    /// the result of each one in order. `Set` yields the previous value and
+    /// `IncrBy` the new one, with overflows wrapping around. Not
+    /// replicated, see `Redis::call`.
+    pub fn bitfield(&self, ops: &[BitfieldOp]) -> Result<Vec<i64>, RModError> {
+        let key = manifest_redis_string(self.key_str.str_inner)?;
+        let op_args = to_bitfield_args(ops);
+        let mut args: Vec<&str> = Vec::with_capacity(op_args.len() + 1);
+        args.push(key.as_str());
+        args.extend(op_args.iter().map(|a| a.as_str()));
+
+        let redis = Redis::new(self.ctx);
+        let reply = redis.call("bitfield", args.as_slice())?;
+        let size = reply.check_length();
+        let mut results: Vec<i64> = Vec::with_capacity(size);
+        for idx in 0..size {
+            results.push(reply.reply_array_element(idx)?.to_integer()?);
+        }
+        Ok(results)
+    }
+
+    /// Deletes a field from a hash key, returning whether the field existed.
+    pub fn rm_hdel(&self, field: &str) -> Result<bool, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(false),
+            raw::KeyType::Hash => (),
+            _ => return Err(error!("Error while hdel to key, not Hash structure")),
+        }
+
+        let fld_str = RedisString::create(self.ctx, field);
+        let deleted = unsafe { raw::rm_hash_del(self.key_inner, raw::HashFlags::NONE, fld_str.str_inner) };
+        Ok(deleted > 0)
+    }
+
+    /// Returns every field/value pair of a hash key, or an empty vector if the
+    /// key doesn't exist.
+    pub fn rm_hgetall(&self) -> Result<Vec<(String, String)>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(Vec::new()),
+            raw::KeyType::Hash => (),
+            _ => return Err(error!("Error while hgetall to key, not Hash structure")),
+        }
+
+        let mut pairs = Vec::new();
+        self.hscan(|field, value| pairs.push((field.to_string(), value.unwrap_or_default().to_string())))?;
+        Ok(pairs)
+    }
+
+    /// Calls `f` with every field and value of a hash key, every member of a
+    /// set key (with no value) or every member and score of a sorted set key.
+    /// Does nothing if the key doesn't exist.
+    ///
+    /// Unlike `rm_hgetall`, this walks the key with a cursor instead of
+    /// collecting everything first, so it's suited to large keys. `f` mustn't
+    /// modify the key. Requires Redis 6 or later.
+    pub fn hscan<F>(&self, mut f: F) -> Result<(), RModError>
+    where
+        F: FnMut(&str, Option<&str>),
+    {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(()),
+            raw::KeyType::Hash | raw::KeyType::Set | raw::KeyType::Zset => (),
+            _ => return Err(error!("Error while hscan to key, not Hash, Set or Zset structure")),
+        }
+
+        let cursor = raw::scan_cursor_create()
+            .ok_or_else(|| error!("Scanning keys requires Redis 6 or later"))?;
+        let mut state = KeyScanState { f: &mut f, error: None };
+        while raw::scan_key(
+            self.key_inner,
+            cursor,
+            Some(key_scan_callback),
+            &mut state as *mut KeyScanState as *mut c_void,
+        ) != 0
+            && state.error.is_none()
+        {}
+        raw::scan_cursor_destroy(cursor);
+
+        match state.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Drop for RedisKeyWritable {
@@ -491,6 +2124,100 @@ impl Drop for RedisKeyWritable {
     }
 }
 
+// ZsetRange is a range iteration over a sorted set, which is stopped as it
+// goes out of scope so that the key is never left with an iterator open.
+struct ZsetRange {
+    key_inner: *mut raw::RedisModuleKey,
+}
+
+impl ZsetRange {
+    fn first_in_score_range(
+        key_inner: *mut raw::RedisModuleKey,
+        min: f64,
+        max: f64,
+    ) -> Result<ZsetRange, RModError> {
+        match raw::zset_first_in_score_range(key_inner, min, max, 0, 0) {
+            raw::Status::Ok => Ok(ZsetRange { key_inner }),
+            raw::Status::Err => Err(error!("Error while starting range over key, not Zset structure")),
+        }
+    }
+
+    fn end_reached(&self) -> bool {
+        raw::zset_range_end_reached(self.key_inner) != 0
+    }
+
+    fn current_element(&self, ctx: *mut raw::RedisModuleCtx) -> Result<(String, f64), RModError> {
+        let mut score: f64 = 0.0;
+        let str_inner = raw::zset_range_current_element(self.key_inner, &mut score);
+        if str_inner.is_null() {
+            return Err(error!("Error while reading range element of key"));
+        }
+        let member = RedisString { ctx, str_inner };
+        Ok((manifest_redis_string(member.str_inner)?, score))
+    }
+
+    fn next(&self) -> bool {
+        raw::zset_range_next(self.key_inner) != 0
+    }
+}
+
+impl Drop for ZsetRange {
+    fn drop(&mut self) {
+        raw::zset_range_stop(self.key_inner);
+    }
+}
+
+// StreamIterator is an iteration over the entries of a stream, which is
+// stopped as it goes out of scope like a ZsetRange.
+struct StreamIterator {
+    key_inner: *mut raw::RedisModuleKey,
+}
+
+impl StreamIterator {
+    fn start(
+        key_inner: *mut raw::RedisModuleKey,
+        start: &mut raw::RedisModuleStreamID,
+        end: &mut raw::RedisModuleStreamID,
+    ) -> Result<StreamIterator, RModError> {
+        match raw::stream_iterator_start(key_inner, 0, start, end) {
+            Some(raw::Status::Ok) => Ok(StreamIterator { key_inner }),
+            Some(raw::Status::Err) => Err(error!("Error while starting iteration over key, not Stream structure")),
+            None => Err(error!("Streams require Redis 6.2 or later")),
+        }
+    }
+
+    // Moves to the next entry, returning its ID and number of fields, or
+    // `None` once there are no more entries.
+    fn next_id(&self) -> Option<(raw::RedisModuleStreamID, usize)> {
+        let mut id = raw::RedisModuleStreamID::default();
+        let mut numfields: c_long = 0;
+        match raw::stream_iterator_next_id(self.key_inner, &mut id, &mut numfields) {
+            Some(raw::Status::Ok) => Some((id, numfields as usize)),
+            _ => None,
+        }
+    }
+
+    // Returns the next field/value pair of the current entry, or `None` once
+    // there are no more.
+    fn next_field(&self, ctx: *mut raw::RedisModuleCtx) -> Result<Option<(String, String)>, RModError> {
+        let mut field_inner: *mut raw::RedisModuleString = ptr::null_mut();
+        let mut value_inner: *mut raw::RedisModuleString = ptr::null_mut();
+        match raw::stream_iterator_next_field(self.key_inner, &mut field_inner, &mut value_inner) {
+            Some(raw::Status::Ok) => (),
+            _ => return Ok(None),
+        }
+        let field = RedisString { ctx, str_inner: field_inner };
+        let value = RedisString { ctx, str_inner: value_inner };
+        Ok(Some((manifest_redis_string(field.str_inner)?, manifest_redis_string(value.str_inner)?)))
+    }
+}
+
+impl Drop for StreamIterator {
+    fn drop(&mut self) {
+        raw::stream_iterator_stop(self.key_inner);
+    }
+}
+
 /// `RedisString` is an abstraction over a Redis string.
 ///
 /// Its primary function is to ensure the proper deallocation of resources when
@@ -509,6 +2236,42 @@ impl RedisString {
         let str_inner = raw::create_string(ctx, format!("{}\0", s).as_ptr(), s.len());
         RedisString { ctx, str_inner }
     }
+
+    // Creates a string holding arbitrary bytes, which Redis copies as is.
+    fn create_from_bytes(ctx: *mut raw::RedisModuleCtx, bytes: &[u8]) -> RedisString {
+        let str_inner = raw::create_string(ctx, bytes.as_ptr(), bytes.len());
+        RedisString { ctx, str_inner }
+    }
+
+    /// Returns the length of the string in bytes without copying it.
+    pub fn len(&self) -> usize {
+        let mut length: size_t = 0;
+        raw::string_ptr_len(self.str_inner, &mut length);
+        length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes of the string without copying them.
+    pub fn as_bytes(&self) -> &[u8] {
+        let mut length: size_t = 0;
+        let bytes = raw::string_ptr_len(self.str_inner, &mut length);
+        unsafe { std::slice::from_raw_parts(bytes, length) }
+    }
+
+    /// Returns the string as UTF-8, with invalid sequences replaced by
+    /// `U+FFFD`.
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self.as_bytes()).into_owned()
+    }
+}
+
+impl fmt::Display for RedisString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
+    }
 }
 
 impl Drop for RedisString {
@@ -529,35 +2292,94 @@ impl RedisCallReply {
         RedisCallReply{ reply }
     }
 
-    fn check_type(&self) -> raw::ReplyType {
+    pub fn check_type(&self) -> raw::ReplyType {
         raw::call_reply_type(self.reply)
     }
 
-    fn to_integer(&self) -> Result<i64, RModError> {
+    pub fn to_integer(&self) -> Result<i64, RModError> {
         if self.check_type() != raw::ReplyType::Integer {
             return Err(error!("Invalid type of CallReply, not Integer"))
         }
         Ok(raw::call_reply_integer(self.reply) as i64)
     }
 
-    fn to_string(&self) -> Result<String, RModError> {
+    /// Returns the bytes of a string reply as they are, for binary values
+    /// like the output of `DUMP` that `to_string` would reject.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RModError> {
         if self.check_type() != raw::ReplyType::String {
             return Err(error!("Invalid type of CallReply, not String"))
         }
 
         let mut length: size_t = 0;
         let char_ptr = raw::call_reply_string_ptr(self.reply, &mut length);
-        match from_byte_string(char_ptr, length){
+        Ok(unsafe { std::slice::from_raw_parts(char_ptr, length) }.to_vec())
+    }
+
+    pub fn to_string(&self) -> Result<String, RModError> {
+        match String::from_utf8(self.to_bytes()?) {
             Ok(s) => Ok(s),
             Err(_) => Err(error!("failed to parse char pointer"))
         }
     }
 
-    fn check_length(&self) -> size_t {
+    /// Returns the value of a double reply. A string reply holding a number,
+    /// which is how doubles (e.g. from `ZSCORE`) are sent under RESP2, is
+    /// parsed as well.
+    pub fn to_double(&self) -> Result<f64, RModError> {
+        match self.check_type() {
+            raw::ReplyType::Double => match raw::call_reply_double(self.reply) {
+                Some(d) => Ok(d),
+                None => Err(error!("Double replies require Redis 7 or later")),
+            },
+            raw::ReplyType::String => Ok(self.to_string()?.parse::<f64>()?),
+            _ => Err(error!("Invalid type of CallReply, not Double")),
+        }
+    }
+
+    pub fn check_length(&self) -> size_t {
         raw::call_reply_length(self.reply)
     }
 
-    fn reply_array_element(&self, idx: size_t) -> Result<RedisCallReply, RModError> {
+    /// Converts the reply to a `Reply`. An error reply becomes an `Err`
+    /// carrying the error code and message Redis replied with.
+    pub fn to_reply(&self) -> Result<Reply, RModError> {
+        match self.check_type() {
+            raw::ReplyType::String => Ok(Reply::String(self.to_string()?)),
+            raw::ReplyType::Integer => Ok(Reply::Integer(self.to_integer()?)),
+            raw::ReplyType::Array => {
+                let elements = (0..self.check_length())
+                    .map(|idx| self.reply_array_element(idx)?.to_reply())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Reply::Array(elements))
+            }
+            raw::ReplyType::Nil => Ok(Reply::Nil),
+            raw::ReplyType::Double => Ok(Reply::Double(self.to_double()?)),
+            raw::ReplyType::Bool => match raw::call_reply_bool(self.reply) {
+                Some(b) => Ok(Reply::Bool(b != 0)),
+                None => Err(error!("Bool replies require Redis 7 or later")),
+            },
+            // The remaining RESP3 replies have no `Reply` counterpart yet.
+            raw::ReplyType::Map
+            | raw::ReplyType::Set
+            | raw::ReplyType::BigNumber
+            | raw::ReplyType::Unknown => Ok(Reply::Unknown),
+            raw::ReplyType::Error => Err(parse_error_reply(&self.to_error()?)),
+        }
+    }
+
+    /// Returns the message of an error reply, starting with its error code
+    /// (e.g. `ERR wrong number of arguments for 'get' command`).
+    pub fn to_error(&self) -> Result<String, RModError> {
+        if self.check_type() != raw::ReplyType::Error {
+            return Err(error!("Invalid type of CallReply, not Error"))
+        }
+
+        let mut length: size_t = 0;
+        let char_ptr = raw::call_reply_string_ptr(self.reply, &mut length);
+        Ok(from_byte_string(char_ptr, length)?)
+    }
+
+    pub fn reply_array_element(&self, idx: size_t) -> Result<RedisCallReply, RModError> {
         if self.check_type() != raw::ReplyType::Array {
             return Err(error!("Invalid type of CallReply, not Array"))
         }
@@ -598,6 +2420,14 @@ pub fn enable_redis_allocator(){
 
 
 
+// Redis errors start with their code, e.g. `ERR no such key`.
+fn parse_error_reply(message: &str) -> RModError {
+    match message.find(' ') {
+        Some(idx) => RModError::with_code(&message[..idx], &message[idx + 1..]),
+        None => RModError::with_code(message, ""),
+    }
+}
+
 fn handle_status(status: raw::Status, message: &str) -> Result<(), RModError> {
     match status {
         raw::Status::Ok => Ok(()),
@@ -615,7 +2445,35 @@ fn manifest_redis_string(
     from_byte_string(bytes, length)
 }
 
-fn parse_args(
+// Carries the callback of `hscan` through `RedisModule_ScanKey`, along with
+// the first error met since the callback itself can't return one.
+struct KeyScanState<'a> {
+    f:     &'a mut dyn FnMut(&str, Option<&str>),
+    error: Option<RModError>,
+}
+
+extern "C" fn key_scan_callback(
+    _key: *mut raw::RedisModuleKey,
+    field: *mut raw::RedisModuleString,
+    value: *mut raw::RedisModuleString,
+    privdata: *mut c_void,
+) {
+    let state = unsafe { &mut *(privdata as *mut KeyScanState) };
+    if state.error.is_some() {
+        return;
+    }
+    let value = if value.is_null() {
+        Ok(None)
+    } else {
+        manifest_redis_string(value).map(Some)
+    };
+    match (manifest_redis_string(field), value) {
+        (Ok(field), Ok(value)) => (state.f)(&field, value.as_deref()),
+        (Err(err), _) | (_, Err(err)) => state.error = Some(err.into()),
+    }
+}
+
+pub(crate) fn parse_args(
     argv: *mut *mut raw::RedisModuleString,
     argc: c_int,
 ) -> Result<Vec<String>, string::FromUtf8Error> {
@@ -640,12 +2498,102 @@ fn from_byte_string(
     String::from_utf8(vec_str)
 }
 
+// Errors unless the key is empty or holds a string, since `string_dma` must
+// not be called on other kinds of values.
+fn check_string_key(key: *mut raw::RedisModuleKey) -> Result<(), RModError> {
+    match raw::key_type(key) {
+        raw::KeyType::Empty | raw::KeyType::String => Ok(()),
+        _ => Err(RModError::with_code(
+            "WRONGTYPE",
+            "Operation against a key holding the wrong kind of value",
+        )),
+    }
+}
+
 fn read_key(key: *mut raw::RedisModuleKey) -> Result<String, string::FromUtf8Error> {
+    String::from_utf8(read_key_bytes(key))
+}
+
+fn read_key_bytes(key: *mut raw::RedisModuleKey) -> Vec<u8> {
     let mut length: size_t = 0;
-    from_byte_string(
-        raw::string_dma(key, &mut length, raw::KeyMode::READ),
-        length,
-    )
+    let dma = raw::string_dma(key, &mut length, raw::KeyMode::READ);
+    unsafe { std::slice::from_raw_parts(dma, length) }.to_vec()
+}
+
+// Returns the part of a key that's hashed to compute its cluster slot, which
+// is the content of the first non-empty `{...}` if there's one.
+fn hash_tag(key: &[u8]) -> &[u8] {
+    if let Some(open) = key.iter().position(|&b| b == b'{') {
+        if let Some(len) = key[open + 1..].iter().position(|&b| b == b'}') {
+            if len > 0 {
+                return &key[open + 1..open + 1 + len];
+            }
+        }
+    }
+    key
+}
+
+// CRC16-CCITT (XMODEM) as used by Redis Cluster.
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in bytes {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+// Parses a stream entry ID as taken by `XRANGE`, where a missing sequence
+// number defaults to `seq`.
+fn parse_stream_id(id: &str, seq: u64) -> Result<raw::RedisModuleStreamID, RModError> {
+    let invalid = || RModError::with_code("ERR", "Invalid stream ID specified as stream command argument");
+    match id {
+        "-" => return Ok(raw::RedisModuleStreamID { ms: 0, seq: 0 }),
+        "+" => return Ok(raw::RedisModuleStreamID { ms: u64::MAX, seq: u64::MAX }),
+        _ => (),
+    }
+    let (ms, seq) = match id.split_once('-') {
+        Some((ms, seq)) => (ms, seq.parse::<u64>().map_err(|_| invalid())?),
+        None => (id, seq),
+    };
+    Ok(raw::RedisModuleStreamID { ms: ms.parse::<u64>().map_err(|_| invalid())?, seq })
+}
+
+fn from_raw_key_type(key_type: raw::KeyType) -> KeyType {
+    match key_type {
+        raw::KeyType::Empty => KeyType::Empty,
+        raw::KeyType::String => KeyType::String,
+        raw::KeyType::List => KeyType::List,
+        raw::KeyType::Hash => KeyType::Hash,
+        raw::KeyType::Set => KeyType::Set,
+        raw::KeyType::Zset => KeyType::Zset,
+        raw::KeyType::Module => KeyType::Module,
+        raw::KeyType::Stream => KeyType::Stream,
+    }
+}
+
+fn to_bitfield_args(ops: &[BitfieldOp]) -> Vec<String> {
+    let mut args: Vec<String> = Vec::with_capacity(ops.len() * 4);
+    for op in ops {
+        match *op {
+            BitfieldOp::Get { ty, offset } => {
+                args.extend(vec!["get".to_string(), ty.to_string(), offset.to_string()]);
+            }
+            BitfieldOp::Set { ty, offset, value } => {
+                args.extend(vec![
+                    "set".to_string(), ty.to_string(), offset.to_string(), value.to_string()
+                ]);
+            }
+            BitfieldOp::IncrBy { ty, offset, increment } => {
+                args.extend(vec![
+                    "incrby".to_string(), ty.to_string(), offset.to_string(), increment.to_string()
+                ]);
+            }
+        }
+    }
+    args
 }
 
 fn to_raw_mode(mode: KeyMode) -> raw::KeyMode {
@@ -654,3 +2602,55 @@ fn to_raw_mode(mode: KeyMode) -> raw::KeyMode {
         KeyMode::ReadWrite => raw::KeyMode::READ | raw::KeyMode::WRITE,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, hash_tag, is_error_code, rmod_error_message, LogLevel};
+    use crate::error::RModError;
+
+    #[test]
+    fn error_message_skips_wrapping_variants() {
+        let e = RModError::from("x".parse::<i64>().unwrap_err());
+        assert_eq!(rmod_error_message(&e), "ERR invalid digit found in string");
+
+        let e = RModError::with_code("WRONGTYPE", "Operation against a key holding the wrong kind of value");
+        assert_eq!(
+            rmod_error_message(&e),
+            "WRONGTYPE Operation against a key holding the wrong kind of value"
+        );
+    }
+
+    #[test]
+    fn error_codes_are_single_uppercase_words() {
+        assert!(is_error_code("ERR"));
+        assert!(is_error_code("WRONGTYPE"));
+        assert!(is_error_code("CROSSSLOT"));
+        assert!(!is_error_code(""));
+        assert!(!is_error_code("err"));
+        assert!(!is_error_code("NOT OK"));
+        assert!(!is_error_code("ERR\n"));
+    }
+
+    #[test]
+    fn slots_hash_the_tag_like_redis() {
+        let slot = |key: &str| crc16(hash_tag(key.as_bytes())) & 0x3FFF;
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(slot("foo"), 12182);
+        assert_eq!(slot("bar"), 5061);
+
+        assert_eq!(hash_tag(b"{user1000}.following"), b"user1000");
+        assert_eq!(slot("{user1000}.following"), slot("{user1000}.followers"));
+        assert_eq!(hash_tag(b"foo{}{bar}"), b"foo{}{bar}");
+        assert_eq!(hash_tag(b"foo{{bar}}zap"), b"{bar");
+        assert_eq!(hash_tag(b"foo{bar}{zap}"), b"bar");
+    }
+
+    #[test]
+    fn log_levels_are_named_like_redis() {
+        assert_eq!(LogLevel::Debug.as_str(), "debug");
+        assert_eq!(LogLevel::Verbose.as_str(), "verbose");
+        assert_eq!(LogLevel::Notice.as_str(), "notice");
+        assert_eq!(LogLevel::Warning.as_str(), "warning");
+        assert_eq!(LogLevel::Warning.as_c_str(), "warning\0");
+    }
+}
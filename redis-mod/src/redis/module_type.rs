@@ -0,0 +1,284 @@
+use crate::error::RModError;
+use crate::redis::{raw, RedisKeyWritable, RedisString};
+use libc::size_t;
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+
+/// `DataType` is implemented by a Rust structure that is stored in the
+/// keyspace as a custom module type and persisted through RDB.
+///
+/// Values are owned by Redis once they're set on a key, and are dropped when
+/// Redis frees them (e.g. on `DEL` or when the key is overwritten).
+pub trait DataType: Sized {
+    /// Reads back a value written by `rdb_save`. `encver` is the encoding
+    /// version the value was saved with, which may be older than the current
+    /// one.
+    fn rdb_load(io: &mut ModuleIO, encver: i32) -> Result<Self, RModError>;
+
+    /// Writes the value out to an RDB file.
+    fn rdb_save(&self, io: &mut ModuleIO);
+
+    /// Emits the commands that recreate the value when the AOF is rewritten.
+    /// Nothing is emitted by default, which is fine for modules that don't
+    /// need to support AOF.
+    fn aof_rewrite(&self, _aof: &mut ModuleIO, _key: &str) -> Result<(), RModError> {
+        Ok(())
+    }
+
+    /// Feeds the value to `DEBUG DIGEST` and `DEBUG DIGEST-VALUE`, which are
+    /// used to check that a value is identical on a master and its replicas.
+    /// Nothing is added by default, so the value doesn't affect the digest.
+    fn digest(&self, _digest: &mut ModuleDigest) {}
+
+    /// Reports roughly how much memory the value uses for `MEMORY USAGE`.
+    fn mem_usage(&self) -> usize {
+        0
+    }
+}
+
+/// `ModuleIO` wraps the handle given to RDB and AOF callbacks.
+pub struct ModuleIO {
+    io: *mut raw::RedisModuleIO,
+}
+
+impl ModuleIO {
+    pub fn save_unsigned(&mut self, value: u64) {
+        raw::save_unsigned(self.io, value)
+    }
+
+    pub fn load_unsigned(&mut self) -> u64 {
+        raw::load_unsigned(self.io)
+    }
+
+    pub fn save_signed(&mut self, value: i64) {
+        raw::save_signed(self.io, value)
+    }
+
+    pub fn load_signed(&mut self) -> i64 {
+        raw::load_signed(self.io)
+    }
+
+    pub fn save_double(&mut self, value: f64) {
+        raw::save_double(self.io, value)
+    }
+
+    pub fn load_double(&mut self) -> f64 {
+        raw::load_double(self.io)
+    }
+
+    pub fn save_string(&mut self, value: &str) {
+        raw::save_string_buffer(self.io, value.as_ptr(), value.len())
+    }
+
+    pub fn load_string(&mut self) -> Result<String, RModError> {
+        let mut length: size_t = 0;
+        let buf = raw::load_string_buffer(self.io, &mut length);
+        if buf.is_null() {
+            return Err(error!("Error while loading string from RDB"));
+        }
+        let bytes = unsafe { slice::from_raw_parts(buf, length) }.to_vec();
+        raw::rm_free(buf);
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    /// Emits a command into the rewritten AOF. Only meaningful from within
+    /// `DataType::aof_rewrite`.
+    pub fn emit_aof(&mut self, cmdname: &str, args: &[&str]) -> Result<(), RModError> {
+        let cmd = CString::new(cmdname)?;
+        // There's no context while rewriting, so the strings are created
+        // without one and freed by `RedisString` as usual.
+        let strings: Vec<RedisString> = args
+            .iter()
+            .map(|arg| RedisString::create(ptr::null_mut(), arg))
+            .collect();
+        let mut argv: Vec<*mut raw::RedisModuleString> =
+            strings.iter().map(|s| s.str_inner).collect();
+        // `cmd` and `argv` both outlive the call.
+        unsafe { raw::emit_aof_argv(self.io, cmd.as_ptr(), argv.as_mut_ptr(), argv.len()) };
+        Ok(())
+    }
+}
+
+/// `ModuleDigest` accumulates the digest of a value in `DataType::digest`.
+///
+/// Elements added between two calls to `end_sequence` are digested as an
+/// ordered sequence, and the sequences themselves are digested unordered.
+/// So a hash-like value would add each field and value, ending a sequence
+/// after every pair, while a list-like value would add all of its elements
+/// and end a single sequence.
+pub struct ModuleDigest {
+    digest: *mut raw::RedisModuleDigest,
+}
+
+impl ModuleDigest {
+    pub fn add_string_buffer(&mut self, ele: &[u8]) {
+        raw::digest_add_string_buffer(self.digest, ele.as_ptr(), ele.len())
+    }
+
+    pub fn add_long_long(&mut self, ele: i64) {
+        raw::digest_add_long_long(self.digest, ele)
+    }
+
+    pub fn end_sequence(&mut self) {
+        raw::digest_end_sequence(self.digest)
+    }
+}
+
+/// `ModuleType` is the handle to a custom type created with
+/// `RedisModule_CreateDataType`. It's needed to set or get values of the
+/// type on keys, so it's usually kept for the lifetime of the module.
+pub struct ModuleType<T: DataType> {
+    inner:   *mut raw::RedisModuleType,
+    phantom: PhantomData<T>,
+}
+
+// The type pointer is never written to after creation and Redis keeps it
+// alive until the module is unloaded.
+unsafe impl<T: DataType> Send for ModuleType<T> {}
+unsafe impl<T: DataType> Sync for ModuleType<T> {}
+
+impl<T: DataType> ModuleType<T> {
+    pub fn builder(name: &str) -> ModuleTypeBuilder<T> {
+        ModuleTypeBuilder {
+            name:    name.to_string(),
+            encver:  0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// `ModuleTypeBuilder` registers a `DataType` with Redis. It must be used
+/// from within `RedisModule_OnLoad`.
+pub struct ModuleTypeBuilder<T: DataType> {
+    name:    String,
+    encver:  i32,
+    phantom: PhantomData<T>,
+}
+
+impl<T: DataType> ModuleTypeBuilder<T> {
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the encoding version that's passed back to `rdb_load`. Bump it
+    /// whenever the RDB format of the type changes.
+    pub fn encoding_version(mut self, encver: i32) -> ModuleTypeBuilder<T> {
+        self.encver = encver;
+        self
+    }
+
+    /// Creates the type. Redis requires the name to be exactly 9 characters
+    /// from `A-Z`, `a-z`, `0-9`, `-` and `_`, and unique across modules.
+    pub fn create(self, ctx: *mut raw::RedisModuleCtx) -> Result<ModuleType<T>, RModError> {
+        if self.name.len() != 9 {
+            return Err(error!("Module type name must be exactly 9 characters"));
+        }
+        let name = CString::new(self.name)?;
+        let mut methods = raw::RedisModuleTypeMethods {
+            version:     raw::REDISMODULE_TYPE_METHOD_VERSION,
+            rdb_load:    Some(rdb_load::<T>),
+            rdb_save:    Some(rdb_save::<T>),
+            aof_rewrite: Some(aof_rewrite::<T>),
+            mem_usage:   Some(mem_usage::<T>),
+            digest:      Some(digest::<T>),
+            free:        Some(free::<T>),
+        };
+        let inner = raw::create_data_type(ctx, name.as_ptr() as *const u8, self.encver, &mut methods);
+        if inner.is_null() {
+            return Err(error!("Error while creating module type"));
+        }
+        Ok(ModuleType {
+            inner,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl RedisKeyWritable {
+    /// Stores `value` on the key, replacing whatever the key held before.
+    pub fn set_module_value<T: DataType>(
+        &self,
+        module_type: &ModuleType<T>,
+        value: T,
+    ) -> Result<(), RModError> {
+        let value = Box::into_raw(Box::new(value));
+        let status = raw::module_type_set_value(self.key_inner, module_type.inner, value as *mut c_void);
+        if status == raw::Status::Err {
+            // Redis didn't take ownership, so the value is still ours to free.
+            drop(unsafe { Box::from_raw(value) });
+            return Err(error!("Error while setting module value to key"));
+        }
+        Ok(())
+    }
+
+    /// Gets the value of the key. `None` is returned for an empty key, and an
+    /// error for a key of any other type.
+    pub fn get_module_value<T: DataType>(
+        &self,
+        module_type: &ModuleType<T>,
+    ) -> Result<Option<&T>, RModError> {
+        Ok(self.module_value(module_type)?.map(|value| unsafe { &*value }))
+    }
+
+    /// Same as `get_module_value`, but allows the value to be modified in
+    /// place.
+    pub fn get_module_value_mut<T: DataType>(
+        &mut self,
+        module_type: &ModuleType<T>,
+    ) -> Result<Option<&mut T>, RModError> {
+        Ok(self.module_value(module_type)?.map(|value| unsafe { &mut *value }))
+    }
+
+    fn module_value<T: DataType>(&self, module_type: &ModuleType<T>) -> Result<Option<*mut T>, RModError> {
+        match raw::key_type(self.key_inner) {
+            raw::KeyType::Empty => return Ok(None),
+            raw::KeyType::Module => (),
+            _ => return Err(error!("Error while getting module value from key, not Module structure")),
+        }
+        if raw::module_type_get_type(self.key_inner) != module_type.inner {
+            return Err(error!("Error while getting module value from key, another Module type"));
+        }
+        Ok(Some(raw::module_type_get_value(self.key_inner) as *mut T))
+    }
+}
+
+extern "C" fn rdb_load<T: DataType>(rdb: *mut raw::RedisModuleIO, encver: i32) -> *mut c_void {
+    match T::rdb_load(&mut ModuleIO { io: rdb }, encver) {
+        Ok(value) => Box::into_raw(Box::new(value)) as *mut c_void,
+        // Returning NULL tells Redis that the RDB couldn't be loaded.
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+extern "C" fn rdb_save<T: DataType>(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
+    let value = unsafe { &*(value as *mut T) };
+    value.rdb_save(&mut ModuleIO { io: rdb });
+}
+
+extern "C" fn aof_rewrite<T: DataType>(
+    aof: *mut raw::RedisModuleIO,
+    key: *mut raw::RedisModuleString,
+    value: *mut c_void,
+) {
+    let value = unsafe { &*(value as *mut T) };
+    if let Ok(key) = super::manifest_redis_string(key) {
+        let _ = value.aof_rewrite(&mut ModuleIO { io: aof }, key.as_str());
+    }
+}
+
+extern "C" fn digest<T: DataType>(md: *mut raw::RedisModuleDigest, value: *mut c_void) {
+    let value = unsafe { &*(value as *mut T) };
+    value.digest(&mut ModuleDigest { digest: md });
+}
+
+extern "C" fn mem_usage<T: DataType>(value: *const c_void) -> size_t {
+    let value = unsafe { &*(value as *const T) };
+    value.mem_usage()
+}
+
+extern "C" fn free<T: DataType>(value: *mut c_void) {
+    drop(unsafe { Box::from_raw(value as *mut T) });
+}
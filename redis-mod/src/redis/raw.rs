@@ -4,20 +4,125 @@
 
 extern crate libc;
 
-use libc::{c_int, c_long, c_longlong, size_t};
-use std::os::raw::c_void;
+use libc::{c_int, c_long, c_longlong, c_uint, c_ulonglong, size_t};
+use std::os::raw::{c_char, c_void};
+
+// Functions added to the module API after Redis 5 are declared as `Option`s
+// since they're left NULL when the module is loaded by an older server. Their
+// wrappers return `None` then, and are marked with the version that added them.
 
 // Rust can't link against C macros (#define) so we just redefine them here.
 // There's a ~0 chance that any of these will ever change so it's pretty safe.
 pub const REDISMODULE_APIVER_1: c_int = 1;
+pub const REDISMODULE_NO_EXPIRE: c_longlong = -1;
+pub const REDISMODULE_POSTPONED_ARRAY_LEN: c_long = -1;
 
 bitflags! {
     pub struct KeyMode: c_int {
         const READ = 1;
         const WRITE = (1 << 1);
+
+        // Doesn't update the key's LRU/LFU when opening it.
+        const NOTOUCH = (1 << 16);
+        // Doesn't fire a keyspace miss event when the key is missing, only
+        // exists as of Redis 7.
+        const NONOTIFY = (1 << 17);
+    }
+}
+
+bitflags! {
+    pub struct ContextFlags: c_int {
+        const LUA = 1;
+        const MULTI = (1 << 1);
+        const MASTER = (1 << 2);
+        const SLAVE = (1 << 3);
+        const READONLY = (1 << 4);
+        const CLUSTER = (1 << 5);
+        const AOF = (1 << 6);
+        const RDB = (1 << 7);
+        const MAXMEMORY = (1 << 8);
+        const EVICT = (1 << 9);
+        const OOM = (1 << 10);
+        const OOM_WARNING = (1 << 11);
+        const REPLICATED = (1 << 12);
+        const LOADING = (1 << 13);
+        const REPLICA_IS_STALE = (1 << 14);
+        const REPLICA_IS_CONNECTING = (1 << 15);
+        const REPLICA_IS_TRANSFERRING = (1 << 16);
+        const REPLICA_IS_ONLINE = (1 << 17);
+        const ACTIVE_CHILD = (1 << 18);
+        const MULTI_DIRTY = (1 << 19);
+        const IS_CHILD = (1 << 20);
+        const DENY_BLOCKING = (1 << 21);
+        const RESP3 = (1 << 22);
+        const ASYNC_LOADING = (1 << 23);
+    }
+}
+
+bitflags! {
+    pub struct HashFlags: c_int {
+        const NONE = 0;
+        const NX = 1;
+        const XX = (1 << 1);
+        const CFIELDS = (1 << 2);
+        const EXISTS = (1 << 3);
+    }
+}
+
+bitflags! {
+    pub struct KeySpecFlags: u64 {
+        const RO = 1;
+        const RW = (1 << 1);
+        const OW = (1 << 2);
+        const RM = (1 << 3);
+        const ACCESS = (1 << 4);
+        const UPDATE = (1 << 5);
+        const INSERT = (1 << 6);
+        const DELETE = (1 << 7);
+        const NOT_KEY = (1 << 8);
+        const INCOMPLETE = (1 << 9);
+        const VARIABLE_FLAGS = (1 << 10);
+    }
+}
+
+bitflags! {
+    pub struct ConfigFlags: c_uint {
+        const DEFAULT = 0;
+        const IMMUTABLE = 1;
+        const SENSITIVE = (1 << 1);
+        const HIDDEN = (1 << 4);
+        const PROTECTED = (1 << 5);
+        const DENY_LOADING = (1 << 6);
+        const MEMORY = (1 << 7);
+    }
+}
+
+bitflags! {
+    pub struct NotifyFlags: c_int {
+        const GENERIC = (1 << 2);
+        const STRING = (1 << 3);
+        const LIST = (1 << 4);
+        const SET = (1 << 5);
+        const HASH = (1 << 6);
+        const ZSET = (1 << 7);
+        const EXPIRED = (1 << 8);
+        const EVICTED = (1 << 9);
+        const STREAM = (1 << 10);
+        // Events of module types, only exists as of Redis 6.
+        const MODULE = (1 << 13);
+        const ALL = Self::GENERIC.bits | Self::STRING.bits | Self::LIST.bits
+            | Self::SET.bits | Self::HASH.bits | Self::ZSET.bits
+            | Self::EXPIRED.bits | Self::EVICTED.bits | Self::STREAM.bits;
     }
 }
 
+bitflags! {
+    pub struct CommandFilterFlags: c_int {
+        const DEFAULT = 0;
+        // Don't filter the commands the module itself calls with `call`.
+        const NOSELF = 1;
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum ReplyType{
@@ -27,9 +132,16 @@ pub enum ReplyType{
     Integer = 2,
     Array = 3,
     Nil = 4,
+    // RESP3 replies, only returned as of Redis 7 by commands called with RESP3.
+    Map = 5,
+    Set = 6,
+    Bool = 7,
+    Double = 8,
+    BigNumber = 9,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
 pub enum Status {
     Ok = 0,
     Err = 1,
@@ -44,6 +156,7 @@ pub enum KeyType{
     Set = 4,
     Zset = 5,
     Module = 6,
+    Stream = 7,
 }
 
 
@@ -63,12 +176,294 @@ pub struct RedisModuleKey;
 #[repr(C)]
 pub struct RedisModuleString;
 
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleScanCursor;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleBlockedClient;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleType;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleIO;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleDigest;
+
+pub const REDISMODULE_TYPE_METHOD_VERSION: u64 = 1;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleCommand;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleInfoCtx;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleCommandFilterCtx;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RedisModuleCommandFilter;
+
+pub const REDISMODULE_STREAM_ADD_AUTOID: c_int = 1 << 0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[repr(C)]
+pub struct RedisModuleStreamID {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+pub const REDISMODULE_CLIENTINFO_VERSION: u64 = 1;
+
+#[repr(C)]
+pub struct RedisModuleClientInfo {
+    pub version: u64,
+    pub flags: u64,
+    pub id: u64,
+    pub addr: [c_char; 46],
+    pub port: u16,
+    pub db: u16,
+}
+
+pub const REDISMODULE_COMMAND_INFO_VERSION: c_int = 1;
+
+#[repr(C)]
+pub struct RedisModuleCommandInfoVersion {
+    pub version: c_int,
+    pub sizeof_historyentry: size_t,
+    pub sizeof_keyspec: size_t,
+    pub sizeof_arg: size_t,
+}
+
+#[repr(C)]
+pub struct RedisModuleCommandHistoryEntry {
+    pub since: *const c_char,
+    pub changes: *const c_char,
+}
+
+#[repr(C)]
+pub struct RedisModuleCommandArg {
+    pub name: *const c_char,
+    pub type_: c_int,
+    pub key_spec_index: c_int,
+    pub token: *const c_char,
+    pub summary: *const c_char,
+    pub since: *const c_char,
+    pub flags: c_int,
+    pub deprecated_since: *const c_char,
+    pub subargs: *mut RedisModuleCommandArg,
+    pub display_text: *const c_char,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub enum KeySpecBeginSearchType {
+    Invalid = 0,
+    Unknown = 1,
+    Index = 2,
+    Keyword = 3,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct KeySpecBeginSearchIndex {
+    pub pos: c_int,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct KeySpecBeginSearchKeyword {
+    pub keyword: *const c_char,
+    pub startfrom: c_int,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union KeySpecBeginSearch {
+    pub index: KeySpecBeginSearchIndex,
+    pub keyword: KeySpecBeginSearchKeyword,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub enum KeySpecFindKeysType {
+    Omitted = 0,
+    Unknown = 1,
+    Range = 2,
+    Keynum = 3,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct KeySpecFindKeysRange {
+    pub lastkey: c_int,
+    pub keystep: c_int,
+    pub limit: c_int,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct KeySpecFindKeysKeynum {
+    pub keynumidx: c_int,
+    pub firstkey: c_int,
+    pub keystep: c_int,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union KeySpecFindKeys {
+    pub range: KeySpecFindKeysRange,
+    pub keynum: KeySpecFindKeysKeynum,
+}
+
+#[repr(C)]
+pub struct RedisModuleCommandKeySpec {
+    pub notes: *const c_char,
+    pub flags: u64,
+    pub begin_search_type: KeySpecBeginSearchType,
+    pub bs: KeySpecBeginSearch,
+    pub find_keys_type: KeySpecFindKeysType,
+    pub fk: KeySpecFindKeys,
+}
+
+#[repr(C)]
+pub struct RedisModuleCommandInfo {
+    pub version: *const RedisModuleCommandInfoVersion,
+    pub summary: *const c_char,
+    pub complexity: *const c_char,
+    pub since: *const c_char,
+    pub history: *mut RedisModuleCommandHistoryEntry,
+    pub tips: *const c_char,
+    pub arity: c_int,
+    pub key_specs: *mut RedisModuleCommandKeySpec,
+    pub args: *mut RedisModuleCommandArg,
+}
+
+pub type RedisModuleTypeLoadFunc =
+    extern "C" fn(rdb: *mut RedisModuleIO, encver: c_int) -> *mut c_void;
+pub type RedisModuleTypeSaveFunc =
+    extern "C" fn(rdb: *mut RedisModuleIO, value: *mut c_void);
+pub type RedisModuleTypeRewriteFunc =
+    extern "C" fn(aof: *mut RedisModuleIO, key: *mut RedisModuleString, value: *mut c_void);
+pub type RedisModuleTypeMemUsageFunc =
+    extern "C" fn(value: *const c_void) -> size_t;
+pub type RedisModuleTypeDigestFunc =
+    extern "C" fn(digest: *mut RedisModuleDigest, value: *mut c_void);
+pub type RedisModuleTypeFreeFunc =
+    extern "C" fn(value: *mut c_void);
+
+#[repr(C)]
+pub struct RedisModuleTypeMethods {
+    pub version: u64,
+    pub rdb_load: Option<RedisModuleTypeLoadFunc>,
+    pub rdb_save: Option<RedisModuleTypeSaveFunc>,
+    pub aof_rewrite: Option<RedisModuleTypeRewriteFunc>,
+    pub mem_usage: Option<RedisModuleTypeMemUsageFunc>,
+    pub digest: Option<RedisModuleTypeDigestFunc>,
+    pub free: Option<RedisModuleTypeFreeFunc>,
+}
+
 pub type RedisModuleCmdFunc = extern "C" fn(
      ctx: *mut RedisModuleCtx,
      argv: *mut *mut RedisModuleString,
      argc: c_int,
  ) -> Status;
 
+pub type RedisModuleFreePrivDataFunc = extern "C" fn(
+     ctx: *mut RedisModuleCtx,
+     privdata: *mut c_void,
+ );
+
+pub type RedisModuleTimerProc = extern "C" fn(
+     ctx: *mut RedisModuleCtx,
+     data: *mut c_void,
+ );
+
+pub type RedisModuleTimerID = u64;
+
+pub type RedisModuleScanCB = extern "C" fn(
+     ctx: *mut RedisModuleCtx,
+     keyname: *mut RedisModuleString,
+     key: *mut RedisModuleKey,
+     privdata: *mut c_void,
+ );
+
+pub type RedisModuleScanKeyCB = extern "C" fn(
+     key: *mut RedisModuleKey,
+     field: *mut RedisModuleString,
+     value: *mut RedisModuleString,
+     privdata: *mut c_void,
+ );
+
+pub const REDISMODULE_NODE_ID_LEN: usize = 40;
+
+pub type RedisModuleClusterMessageReceiver = extern "C" fn(
+     ctx: *mut RedisModuleCtx,
+     sender_id: *const c_char,
+     msg_type: u8,
+     payload: *const u8,
+     len: u32,
+ );
+
+pub type RedisModuleCommandFilterFunc = extern "C" fn(filter: *mut RedisModuleCommandFilterCtx);
+
+pub type RedisModuleInfoFunc = extern "C" fn(
+     ctx: *mut RedisModuleInfoCtx,
+     for_crash_report: c_int,
+ );
+
+pub type RedisModuleConfigGetBoolFunc = extern "C" fn(
+     name: *const c_char,
+     privdata: *mut c_void,
+ ) -> c_int;
+
+pub type RedisModuleConfigSetBoolFunc = extern "C" fn(
+     name: *const c_char,
+     val: c_int,
+     privdata: *mut c_void,
+     err: *mut *mut RedisModuleString,
+ ) -> Status;
+
+pub type RedisModuleConfigGetStringFunc = extern "C" fn(
+     name: *const c_char,
+     privdata: *mut c_void,
+ ) -> *mut RedisModuleString;
+
+pub type RedisModuleConfigSetStringFunc = extern "C" fn(
+     name: *const c_char,
+     val: *mut RedisModuleString,
+     privdata: *mut c_void,
+     err: *mut *mut RedisModuleString,
+ ) -> Status;
+
+pub type RedisModuleConfigGetNumericFunc = extern "C" fn(
+     name: *const c_char,
+     privdata: *mut c_void,
+ ) -> c_longlong;
+
+pub type RedisModuleConfigSetNumericFunc = extern "C" fn(
+     name: *const c_char,
+     val: c_longlong,
+     privdata: *mut c_void,
+     err: *mut *mut RedisModuleString,
+ ) -> Status;
+
+pub type RedisModuleConfigApplyFunc = extern "C" fn(
+     ctx: *mut RedisModuleCtx,
+     privdata: *mut c_void,
+     err: *mut *mut RedisModuleString,
+ ) -> Status;
+
 
 //C function wrapper for Rust.
 pub fn init(
@@ -166,10 +561,18 @@ pub fn string_dma(
     unsafe { RedisModule_StringDMA(key, len, mode) }
 }
 
+pub fn string_truncate(key: *mut RedisModuleKey, newlen: size_t) -> Status {
+    unsafe { RedisModule_StringTruncate(key, newlen) }
+}
+
 pub fn delete_key(key: *mut RedisModuleKey) -> Status {
     unsafe { RedisModule_DeleteKey(key) }
 }
 
+pub fn unlink_key(key: *mut RedisModuleKey) -> Status {
+    unsafe { RedisModule_UnlinkKey(key) }
+}
+
 pub fn reply_with_array(
     ctx: *mut RedisModuleCtx,
     len: c_long
@@ -177,10 +580,14 @@ pub fn reply_with_array(
     unsafe { RedisModule_ReplyWithArray(ctx, len) }
 }
 
+pub fn reply_set_array_length(ctx: *mut RedisModuleCtx, len: c_long) {
+    unsafe { RedisModule_ReplySetArrayLength(ctx, len) }
+}
+
 pub fn reply_with_error(
     ctx: *mut RedisModuleCtx,
     err: *const u8
-) {
+) -> Status {
     unsafe { RedisModule_ReplyWithError(ctx, err) }
 }
 
@@ -198,6 +605,10 @@ pub fn reply_with_string(
     unsafe { RedisModule_ReplyWithString(ctx, str) }
 }
 
+pub fn reply_with_string_buffer(ctx: *mut RedisModuleCtx, buf: *const c_char, len: size_t) -> Status {
+    unsafe { RedisModule_ReplyWithStringBuffer(ctx, buf, len) }
+}
+
 pub fn reply_with_simple_string(
     ctx: *mut RedisModuleCtx,
     msg: *const u8
@@ -209,19 +620,52 @@ pub fn reply_with_null(
     ctx: *mut RedisModuleCtx
 ){ unsafe { RedisModule_ReplyWithNull(ctx) } }
 
+// Redis 6.
+pub fn reply_with_null_array(
+    ctx: *mut RedisModuleCtx
+) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithNullArray.map(|reply| reply(ctx)) }
+}
+
+pub fn reply_with_double(ctx: *mut RedisModuleCtx, d: f64) -> Status {
+    unsafe { RedisModule_ReplyWithDouble(ctx, d) }
+}
+
+pub fn reply_with_call_reply(
+    ctx: *mut RedisModuleCtx,
+    reply: *mut RedisModuleCallReply,
+) -> Status {
+    unsafe { RedisModule_ReplyWithCallReply(ctx, reply) }
+}
+
 
 pub fn free_string(ctx: *mut RedisModuleCtx, str: *mut RedisModuleString) {
     unsafe { RedisModule_FreeString(ctx, str) }
 }
 
-pub fn log(ctx: *mut RedisModuleCtx, level: *const u8, fmt: *const u8) {
-    unsafe { RedisModule_Log(ctx, level, fmt) }
+pub fn log(ctx: *mut RedisModuleCtx, level: *const u8, message: *const u8) {
+    // The message is passed as an argument rather than as the format, so
+    // that a `%` in it (e.g. in a key name) isn't taken for a directive.
+    unsafe { RedisModule_Log(ctx, level, "%s\0".as_ptr(), message) }
 }
 
 pub fn replicate_verbatim(ctx: *mut RedisModuleCtx) {
     unsafe { RedisModule_ReplicateVerbatim(ctx) }
 }
 
+/// # Safety
+///
+/// `cmdname` must be NUL-terminated and `argv` must point to `argc` valid
+/// strings.
+pub unsafe fn replicate_argv(
+    ctx: *mut RedisModuleCtx,
+    cmdname: *const i8,
+    argv: *mut *mut RedisModuleString,
+    argc: size_t,
+) -> Status {
+    RedisModule_ReplicateArgv(ctx, cmdname, argv, argc)
+}
+
 pub fn create_string(
     ctx: *mut RedisModuleCtx,
     ptr: *const u8,
@@ -234,10 +678,61 @@ pub fn set_expire(key: *mut RedisModuleKey, expire: c_longlong) -> Status {
     unsafe { RedisModule_SetExpire(key, expire) }
 }
 
+pub fn get_expire(key: *mut RedisModuleKey) -> c_longlong {
+    unsafe { RedisModule_GetExpire(key) }
+}
+
 pub fn string_ptr_len(str: *mut RedisModuleString, len: *mut size_t) -> *const u8 {
     unsafe { RedisModule_StringPtrLen(str, len) }
 }
 
+pub fn string_compare(a: *mut RedisModuleString, b: *mut RedisModuleString) -> c_int {
+    unsafe { RedisModule_StringCompare(a, b) }
+}
+
+pub fn zset_add(
+    key: *mut RedisModuleKey,
+    score: f64,
+    ele: *mut RedisModuleString,
+    flagsptr: *mut c_int,
+) -> Status {
+    unsafe { RedisModule_ZsetAdd(key, score, ele, flagsptr) }
+}
+
+pub fn zset_score(key: *mut RedisModuleKey, ele: *mut RedisModuleString, score: *mut f64) -> Status {
+    unsafe { RedisModule_ZsetScore(key, ele, score) }
+}
+
+pub fn zset_first_in_score_range(
+    key: *mut RedisModuleKey,
+    min: f64,
+    max: f64,
+    minex: c_int,
+    maxex: c_int,
+) -> Status {
+    unsafe { RedisModule_ZsetFirstInScoreRange(key, min, max, minex, maxex) }
+}
+
+pub fn zset_range_current_element(key: *mut RedisModuleKey, score: *mut f64) -> *mut RedisModuleString {
+    unsafe { RedisModule_ZsetRangeCurrentElement(key, score) }
+}
+
+pub fn zset_range_next(key: *mut RedisModuleKey) -> c_int {
+    unsafe { RedisModule_ZsetRangeNext(key) }
+}
+
+pub fn zset_range_end_reached(key: *mut RedisModuleKey) -> c_int {
+    unsafe { RedisModule_ZsetRangeEndReached(key) }
+}
+
+pub fn zset_range_stop(key: *mut RedisModuleKey) {
+    unsafe { RedisModule_ZsetRangeStop(key) }
+}
+
+pub fn value_length(key: *mut RedisModuleKey) -> size_t {
+    unsafe { RedisModule_ValueLength(key) }
+}
+
 pub fn list_push(key: *mut RedisModuleKey, place: c_int, ele: *mut RedisModuleString) -> Status {
     unsafe { RedisModule_ListPush(key, place, ele) }
 }
@@ -282,6 +777,32 @@ pub fn call3_reply(
     unsafe{ RedisModule_Call3(ctx, cmdname, key, arg0, arg1) }
 }
 
+/// # Safety
+///
+/// `cmdname` must be NUL-terminated and `argv` must point to `argc` valid
+/// strings.
+pub unsafe fn call_argv(
+    ctx: *mut RedisModuleCtx,
+    cmdname: *const i8,
+    argv: *mut *mut RedisModuleString,
+    argc: size_t,
+) -> *mut RedisModuleCallReply {
+    RedisModule_CallArgv(ctx, cmdname, argv, argc)
+}
+
+/// # Safety
+///
+/// `cmdname` must be NUL-terminated and `argv` must point to `argc` valid
+/// strings.
+pub unsafe fn call_argv_replicate(
+    ctx: *mut RedisModuleCtx,
+    cmdname: *const i8,
+    argv: *mut *mut RedisModuleString,
+    argc: size_t,
+) -> *mut RedisModuleCallReply {
+    RedisModule_CallArgvReplicate(ctx, cmdname, argv, argc)
+}
+
 pub fn call_keys(
     ctx: *mut RedisModuleCtx,
     arg0: *const i8
@@ -296,6 +817,18 @@ pub fn rm_hash_get(
     unsafe { RedisModuleHash_Get(key, field) }
 }
 
+/// # Safety
+///
+/// `fields` and `values` must both point to `len` elements.
+pub unsafe fn rm_hash_mget(
+    key: *mut RedisModuleKey,
+    fields: *mut *mut RedisModuleString,
+    values: *mut *mut RedisModuleString,
+    len: size_t
+) {
+    RedisModuleHash_MGet(key, fields, values, len)
+}
+
 pub fn rm_hash_set(
     key: *mut RedisModuleKey,
     field: *mut RedisModuleString,
@@ -304,59 +837,634 @@ pub fn rm_hash_set(
     unsafe { RedisModuleHash_Set(key, field, val) }
 }
 
-pub fn rm_alloc(size: size_t) -> *mut u8 {
-    unsafe { RedisModule_Alloc(size) as *mut u8 }
+// The scan functions, Redis 6.
+// They came together, so once a cursor is created the others are there too.
+pub fn scan_cursor_create() -> Option<*mut RedisModuleScanCursor> {
+    unsafe { RedisModule_ScanCursorCreate.map(|create| create()) }
 }
 
-pub fn rm_free(ptr: *mut u8) {
-    unsafe { RedisModule_Free(ptr as *mut c_void) }
+pub fn scan_cursor_destroy(cursor: *mut RedisModuleScanCursor) {
+    unsafe {
+        if let Some(destroy) = RedisModule_ScanCursorDestroy {
+            destroy(cursor)
+        }
+    }
 }
 
-pub fn auto_memory(ctx: *mut RedisModuleCtx) {
-    unsafe { RedisModule_AutoMemory(ctx) }
+pub fn scan(
+    ctx: *mut RedisModuleCtx,
+    cursor: *mut RedisModuleScanCursor,
+    callback: Option<RedisModuleScanCB>,
+    privdata: *mut c_void,
+) -> c_int {
+    unsafe { RedisModule_Scan.map_or(0, |scan| scan(ctx, cursor, callback, privdata)) }
 }
 
-//extern function of C
-#[allow(improper_ctypes)]
-#[link(name = "redis_mod_callable", kind = "static")]
-extern "C" {
-    pub fn RedisModuleCallable2_ReplyInteger(
-        ctx: *mut RedisModuleCtx,
-        cmdname: *const i8,
-        key: *const i8,
-        arg0: *const i8,
-    ) -> c_longlong;
+pub fn scan_key(
+    key: *mut RedisModuleKey,
+    cursor: *mut RedisModuleScanCursor,
+    callback: Option<RedisModuleScanKeyCB>,
+    privdata: *mut c_void,
+) -> c_int {
+    unsafe { RedisModule_ScanKey.map_or(0, |scan| scan(key, cursor, callback, privdata)) }
+}
 
-    pub fn RedisModule_Call1(
-        ctx: *mut RedisModuleCtx,
-        cmdname: *const i8,
-        key: *const i8
-    ) -> *mut RedisModuleCallReply;
+pub fn block_client(
+    ctx: *mut RedisModuleCtx,
+    reply_callback: Option<RedisModuleCmdFunc>,
+    timeout_callback: Option<RedisModuleCmdFunc>,
+    free_privdata: Option<RedisModuleFreePrivDataFunc>,
+    timeout_ms: c_longlong,
+) -> *mut RedisModuleBlockedClient {
+    unsafe {
+        RedisModule_BlockClient(ctx, reply_callback, timeout_callback, free_privdata, timeout_ms)
+    }
+}
 
-    pub fn RedisModule_Call2(
-        ctx: *mut RedisModuleCtx,
-        cmdname: *const i8,
-        key: *const i8,
-        arg0: *const i8
-    ) -> *mut RedisModuleCallReply;
+pub fn unblock_client(bc: *mut RedisModuleBlockedClient, privdata: *mut c_void) -> Status {
+    unsafe { RedisModule_UnblockClient(bc, privdata) }
+}
 
-    pub fn RedisModule_Call3(
-        ctx: *mut RedisModuleCtx,
-        cmdname: *const i8,
-        key: *const i8,
-        arg0: *const i8,
-        arg1: *const i8
-    ) -> *mut RedisModuleCallReply;
+pub fn get_blocked_client_private_data(ctx: *mut RedisModuleCtx) -> *mut c_void {
+    unsafe { RedisModule_GetBlockedClientPrivateData(ctx) }
+}
 
-    pub fn RedisModule_CallKeys(
-        ctx: *mut RedisModuleCtx,
-        arg0: *const i8
-    ) -> *mut RedisModuleCallReply;
+pub fn create_timer(
+    ctx: *mut RedisModuleCtx,
+    period: c_longlong,
+    callback: Option<RedisModuleTimerProc>,
+    data: *mut c_void,
+) -> RedisModuleTimerID {
+    unsafe { RedisModule_CreateTimer(ctx, period, callback, data) }
+}
 
-    pub fn RedisModuleHash_Get(
-        key: *mut RedisModuleKey,
-        field: *mut RedisModuleString
-    ) -> *mut RedisModuleString;
+pub fn stop_timer(
+    ctx: *mut RedisModuleCtx,
+    id: RedisModuleTimerID,
+    data: *mut *mut c_void,
+) -> Status {
+    unsafe { RedisModule_StopTimer(ctx, id, data) }
+}
+
+pub fn register_cluster_message_receiver(
+    ctx: *mut RedisModuleCtx,
+    msg_type: u8,
+    callback: Option<RedisModuleClusterMessageReceiver>,
+) {
+    unsafe { RedisModule_RegisterClusterMessageReceiver(ctx, msg_type, callback) }
+}
+
+pub fn send_cluster_message(
+    ctx: *mut RedisModuleCtx,
+    target_id: *const c_char,
+    msg_type: u8,
+    msg: *const u8,
+    len: u32,
+) -> Status {
+    unsafe { RedisModule_SendClusterMessage(ctx, target_id, msg_type, msg, len) }
+}
+
+pub fn get_my_cluster_id() -> *const c_char {
+    unsafe { RedisModule_GetMyClusterID() }
+}
+
+pub fn get_cluster_size() -> size_t {
+    unsafe { RedisModule_GetClusterSize() }
+}
+
+pub fn get_context_flags(ctx: *mut RedisModuleCtx) -> c_int {
+    unsafe { RedisModule_GetContextFlags(ctx) }
+}
+
+// Redis 7.
+pub fn list_get(key: *mut RedisModuleKey, index: c_long) -> Option<*mut RedisModuleString> {
+    unsafe { RedisModule_ListGet.map(|list_get| list_get(key, index)) }
+}
+
+// Redis 7.
+pub fn list_set(key: *mut RedisModuleKey, index: c_long, value: *mut RedisModuleString) -> Option<Status> {
+    unsafe { RedisModule_ListSet.map(|list_set| list_set(key, index, value)) }
+}
+
+// Redis 7.
+pub fn list_insert(key: *mut RedisModuleKey, index: c_long, value: *mut RedisModuleString) -> Option<Status> {
+    unsafe { RedisModule_ListInsert.map(|list_insert| list_insert(key, index, value)) }
+}
+
+// Redis 6.2.
+pub fn stream_add(
+    key: *mut RedisModuleKey,
+    flags: c_int,
+    id: *mut RedisModuleStreamID,
+    argv: *mut *mut RedisModuleString,
+    numfields: i64,
+) -> Option<Status> {
+    unsafe { RedisModule_StreamAdd.map(|stream_add| stream_add(key, flags, id, argv, numfields)) }
+}
+
+// Redis 6.2.
+pub fn stream_iterator_start(
+    key: *mut RedisModuleKey,
+    flags: c_int,
+    startid: *mut RedisModuleStreamID,
+    endid: *mut RedisModuleStreamID,
+) -> Option<Status> {
+    unsafe { RedisModule_StreamIteratorStart.map(|start| start(key, flags, startid, endid)) }
+}
+
+// Redis 6.2.
+pub fn stream_iterator_stop(key: *mut RedisModuleKey) -> Option<Status> {
+    unsafe { RedisModule_StreamIteratorStop.map(|stop| stop(key)) }
+}
+
+// Redis 6.2.
+pub fn stream_iterator_next_id(
+    key: *mut RedisModuleKey,
+    id: *mut RedisModuleStreamID,
+    numfields: *mut c_long,
+) -> Option<Status> {
+    unsafe { RedisModule_StreamIteratorNextID.map(|next_id| next_id(key, id, numfields)) }
+}
+
+// Redis 6.2.
+pub fn stream_iterator_next_field(
+    key: *mut RedisModuleKey,
+    field_ptr: *mut *mut RedisModuleString,
+    value_ptr: *mut *mut RedisModuleString,
+) -> Option<Status> {
+    unsafe { RedisModule_StreamIteratorNextField.map(|next_field| next_field(key, field_ptr, value_ptr)) }
+}
+
+// Redis 5.0.5.
+pub fn register_command_filter(
+    ctx: *mut RedisModuleCtx,
+    cb: RedisModuleCommandFilterFunc,
+    flags: CommandFilterFlags,
+) -> Option<*mut RedisModuleCommandFilter> {
+    unsafe { RedisModule_RegisterCommandFilter.map(|register| register(ctx, cb, flags.bits())) }
+}
+
+// Redis 5.0.5.
+pub fn command_filter_args_count(fctx: *mut RedisModuleCommandFilterCtx) -> Option<c_int> {
+    unsafe { RedisModule_CommandFilterArgsCount.map(|args_count| args_count(fctx)) }
+}
+
+// Redis 5.0.5.
+pub fn command_filter_arg_get(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int) -> Option<*mut RedisModuleString> {
+    unsafe { RedisModule_CommandFilterArgGet.map(|arg_get| arg_get(fctx, pos)) }
+}
+
+// Redis 5.0.5.
+pub fn command_filter_arg_insert(
+    fctx: *mut RedisModuleCommandFilterCtx,
+    pos: c_int,
+    arg: *mut RedisModuleString,
+) -> Option<Status> {
+    unsafe { RedisModule_CommandFilterArgInsert.map(|arg_insert| arg_insert(fctx, pos, arg)) }
+}
+
+// Redis 5.0.5.
+pub fn command_filter_arg_replace(
+    fctx: *mut RedisModuleCommandFilterCtx,
+    pos: c_int,
+    arg: *mut RedisModuleString,
+) -> Option<Status> {
+    unsafe { RedisModule_CommandFilterArgReplace.map(|arg_replace| arg_replace(fctx, pos, arg)) }
+}
+
+// Redis 5.0.5.
+pub fn command_filter_arg_delete(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int) -> Option<Status> {
+    unsafe { RedisModule_CommandFilterArgDelete.map(|arg_delete| arg_delete(fctx, pos)) }
+}
+
+// Missing from older servers, so it's nullable like `GetCommand`.
+pub fn notify_keyspace_event(
+    ctx: *mut RedisModuleCtx,
+    types: NotifyFlags,
+    event: *const c_char,
+    key: *mut RedisModuleString,
+) -> Option<Status> {
+    unsafe { RedisModule_NotifyKeyspaceEvent.map(|notify| notify(ctx, types.bits(), event, key)) }
+}
+
+pub fn milliseconds() -> c_longlong {
+    unsafe { RedisModule_Milliseconds() }
+}
+
+// Redis 7.
+pub fn monotonic_microseconds() -> Option<u64> {
+    unsafe { RedisModule_MonotonicMicroseconds.map(|monotonic| monotonic()) }
+}
+
+pub fn get_client_id(ctx: *mut RedisModuleCtx) -> c_ulonglong {
+    unsafe { RedisModule_GetClientId(ctx) }
+}
+
+// Redis 6.
+pub fn get_client_info_by_id(ci: *mut RedisModuleClientInfo, id: u64) -> Option<Status> {
+    unsafe { RedisModule_GetClientInfoById.map(|get_info| get_info(ci as *mut c_void, id)) }
+}
+
+// Redis 7.0.3.
+pub fn get_client_name_by_id(ctx: *mut RedisModuleCtx, id: u64) -> Option<*mut RedisModuleString> {
+    unsafe { RedisModule_GetClientNameById.map(|get_name| get_name(ctx, id)) }
+}
+
+// Redis 6.
+pub fn get_lru(key: *mut RedisModuleKey, lru_idle: *mut c_longlong) -> Option<Status> {
+    unsafe { RedisModule_GetLRU.map(|get_lru| get_lru(key, lru_idle)) }
+}
+
+// Redis 6.
+pub fn get_lfu(key: *mut RedisModuleKey, lfu_freq: *mut c_longlong) -> Option<Status> {
+    unsafe { RedisModule_GetLFU.map(|get_lfu| get_lfu(key, lfu_freq)) }
+}
+
+// Redis 6.
+pub fn db_size(ctx: *mut RedisModuleCtx) -> Option<c_ulonglong> {
+    unsafe { RedisModule_DbSize.map(|db_size| db_size(ctx)) }
+}
+
+// Redis 6.
+pub fn random_key(ctx: *mut RedisModuleCtx) -> Option<*mut RedisModuleString> {
+    unsafe { RedisModule_RandomKey.map(|random_key| random_key(ctx)) }
+}
+
+pub fn get_selected_db(ctx: *mut RedisModuleCtx) -> c_int {
+    unsafe { RedisModule_GetSelectedDb(ctx) }
+}
+
+pub fn select_db(ctx: *mut RedisModuleCtx, newid: c_int) -> Status {
+    unsafe { RedisModule_SelectDb(ctx, newid) }
+}
+
+pub fn get_thread_safe_context(bc: *mut RedisModuleBlockedClient) -> *mut RedisModuleCtx {
+    unsafe { RedisModule_GetThreadSafeContext(bc) }
+}
+
+// Redis 6.0.9.
+pub fn get_detached_thread_safe_context(ctx: *mut RedisModuleCtx) -> Option<*mut RedisModuleCtx> {
+    unsafe { RedisModule_GetDetachedThreadSafeContext.map(|get| get(ctx)) }
+}
+
+pub fn free_thread_safe_context(ctx: *mut RedisModuleCtx) {
+    unsafe { RedisModule_FreeThreadSafeContext(ctx) }
+}
+
+pub fn thread_safe_context_lock(ctx: *mut RedisModuleCtx) {
+    unsafe { RedisModule_ThreadSafeContextLock(ctx) }
+}
+
+pub fn thread_safe_context_unlock(ctx: *mut RedisModuleCtx) {
+    unsafe { RedisModule_ThreadSafeContextUnlock(ctx) }
+}
+
+/// # Safety
+///
+/// `field` must be a valid string.
+pub unsafe fn rm_hash_del(
+    key: *mut RedisModuleKey,
+    flags: HashFlags,
+    field: *mut RedisModuleString
+) -> c_int {
+    RedisModuleHash_Del(key, flags, field)
+}
+
+/// # Safety
+///
+/// `cmdname` must be NUL-terminated and `argv` must point to `argc` valid
+/// strings.
+pub unsafe fn emit_aof_argv(
+    io: *mut RedisModuleIO,
+    cmdname: *const i8,
+    argv: *mut *mut RedisModuleString,
+    argc: size_t,
+) {
+    RedisModule_EmitAOFArgv(io, cmdname, argv, argc)
+}
+
+pub fn create_data_type(
+    ctx: *mut RedisModuleCtx,
+    name: *const u8,
+    encver: c_int,
+    typemethods: *mut RedisModuleTypeMethods,
+) -> *mut RedisModuleType {
+    unsafe { RedisModule_CreateDataType(ctx, name, encver, typemethods) }
+}
+
+pub fn module_type_set_value(
+    key: *mut RedisModuleKey,
+    mt: *mut RedisModuleType,
+    value: *mut c_void,
+) -> Status {
+    unsafe { RedisModule_ModuleTypeSetValue(key, mt, value) }
+}
+
+pub fn module_type_get_type(key: *mut RedisModuleKey) -> *mut RedisModuleType {
+    unsafe { RedisModule_ModuleTypeGetType(key) }
+}
+
+pub fn module_type_get_value(key: *mut RedisModuleKey) -> *mut c_void {
+    unsafe { RedisModule_ModuleTypeGetValue(key) }
+}
+
+pub fn save_unsigned(io: *mut RedisModuleIO, value: u64) {
+    unsafe { RedisModule_SaveUnsigned(io, value) }
+}
+
+pub fn load_unsigned(io: *mut RedisModuleIO) -> u64 {
+    unsafe { RedisModule_LoadUnsigned(io) }
+}
+
+pub fn save_signed(io: *mut RedisModuleIO, value: i64) {
+    unsafe { RedisModule_SaveSigned(io, value) }
+}
+
+pub fn load_signed(io: *mut RedisModuleIO) -> i64 {
+    unsafe { RedisModule_LoadSigned(io) }
+}
+
+pub fn save_string_buffer(io: *mut RedisModuleIO, str: *const u8, len: size_t) {
+    unsafe { RedisModule_SaveStringBuffer(io, str, len) }
+}
+
+pub fn load_string_buffer(io: *mut RedisModuleIO, len: *mut size_t) -> *mut u8 {
+    unsafe { RedisModule_LoadStringBuffer(io, len) }
+}
+
+pub fn save_double(io: *mut RedisModuleIO, value: f64) {
+    unsafe { RedisModule_SaveDouble(io, value) }
+}
+
+pub fn load_double(io: *mut RedisModuleIO) -> f64 {
+    unsafe { RedisModule_LoadDouble(io) }
+}
+
+pub fn digest_add_string_buffer(md: *mut RedisModuleDigest, ele: *const u8, len: size_t) {
+    unsafe { RedisModule_DigestAddStringBuffer(md, ele, len) }
+}
+
+pub fn digest_add_long_long(md: *mut RedisModuleDigest, ele: c_longlong) {
+    unsafe { RedisModule_DigestAddLongLong(md, ele) }
+}
+
+pub fn digest_end_sequence(md: *mut RedisModuleDigest) {
+    unsafe { RedisModule_DigestEndSequence(md) }
+}
+
+// `GetCommand`, `CreateSubcommand` and `SetCommandInfo`, Redis 7.
+pub fn get_command(ctx: *mut RedisModuleCtx, name: *const c_char) -> Option<*mut RedisModuleCommand> {
+    unsafe { RedisModule_GetCommand.map(|get_command| get_command(ctx, name)) }
+}
+
+pub fn create_subcommand(
+    parent: *mut RedisModuleCommand,
+    name: *const u8,
+    cmdfunc: Option<RedisModuleCmdFunc>,
+    strflags: *const u8,
+    firstkey: c_int,
+    lastkey: c_int,
+    keystep: c_int,
+) -> Option<Status> {
+    unsafe {
+        RedisModule_CreateSubcommand.map(|create_subcommand| {
+            create_subcommand(parent, name, cmdfunc, strflags, firstkey, lastkey, keystep)
+        })
+    }
+}
+
+pub fn set_command_info(
+    command: *mut RedisModuleCommand,
+    info: *const RedisModuleCommandInfo,
+) -> Option<Status> {
+    unsafe { RedisModule_SetCommandInfo.map(|set_command_info| set_command_info(command, info)) }
+}
+
+// Redis 6.2.5.
+pub fn get_current_command_name(ctx: *mut RedisModuleCtx) -> Option<*const c_char> {
+    unsafe { RedisModule_GetCurrentCommandName.map(|get_name| get_name(ctx)) }
+}
+
+// Redis 7.
+pub fn cluster_key_slot(key: *mut RedisModuleString) -> Option<c_uint> {
+    unsafe { RedisModule_ClusterKeySlot.map(|key_slot| key_slot(key)) }
+}
+
+// Redis 7.
+pub fn reply_with_map(ctx: *mut RedisModuleCtx, len: c_long) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithMap.map(|reply_with_map| reply_with_map(ctx, len)) }
+}
+
+// Redis 7.
+pub fn reply_with_set(ctx: *mut RedisModuleCtx, len: c_long) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithSet.map(|reply_with_set| reply_with_set(ctx, len)) }
+}
+
+// Redis 7.
+pub fn reply_with_bool(ctx: *mut RedisModuleCtx, b: c_int) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithBool.map(|reply_with_bool| reply_with_bool(ctx, b)) }
+}
+
+// Redis 7.
+pub fn reply_with_big_number(ctx: *mut RedisModuleCtx, bignum: *const c_char, len: size_t) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithBigNumber.map(|reply_with_big_number| reply_with_big_number(ctx, bignum, len)) }
+}
+
+// Redis 7.
+pub fn reply_with_verbatim_string_type(
+    ctx: *mut RedisModuleCtx,
+    buf: *const c_char,
+    len: size_t,
+    ext: *const c_char,
+) -> Option<Status> {
+    unsafe { RedisModule_ReplyWithVerbatimStringType.map(|reply_with_verbatim| reply_with_verbatim(ctx, buf, len, ext)) }
+}
+
+// The config API, Redis 7.
+#[allow(clippy::too_many_arguments)]
+pub fn register_bool_config(
+    ctx: *mut RedisModuleCtx,
+    name: *const c_char,
+    default_val: c_int,
+    flags: c_uint,
+    getfn: RedisModuleConfigGetBoolFunc,
+    setfn: RedisModuleConfigSetBoolFunc,
+    applyfn: Option<RedisModuleConfigApplyFunc>,
+    privdata: *mut c_void,
+) -> Option<Status> {
+    unsafe {
+        RedisModule_RegisterBoolConfig.map(|register| {
+            register(ctx, name, default_val, flags, getfn, setfn, applyfn, privdata)
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_string_config(
+    ctx: *mut RedisModuleCtx,
+    name: *const c_char,
+    default_val: *const c_char,
+    flags: c_uint,
+    getfn: RedisModuleConfigGetStringFunc,
+    setfn: RedisModuleConfigSetStringFunc,
+    applyfn: Option<RedisModuleConfigApplyFunc>,
+    privdata: *mut c_void,
+) -> Option<Status> {
+    unsafe {
+        RedisModule_RegisterStringConfig.map(|register| {
+            register(ctx, name, default_val, flags, getfn, setfn, applyfn, privdata)
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_numeric_config(
+    ctx: *mut RedisModuleCtx,
+    name: *const c_char,
+    default_val: c_longlong,
+    flags: c_uint,
+    min: c_longlong,
+    max: c_longlong,
+    getfn: RedisModuleConfigGetNumericFunc,
+    setfn: RedisModuleConfigSetNumericFunc,
+    applyfn: Option<RedisModuleConfigApplyFunc>,
+    privdata: *mut c_void,
+) -> Option<Status> {
+    unsafe {
+        RedisModule_RegisterNumericConfig.map(|register| {
+            register(ctx, name, default_val, flags, min, max, getfn, setfn, applyfn, privdata)
+        })
+    }
+}
+
+pub fn load_configs(ctx: *mut RedisModuleCtx) -> Option<Status> {
+    unsafe { RedisModule_LoadConfigs.map(|load_configs| load_configs(ctx)) }
+}
+
+// Redis 7.
+pub fn call_reply_double(reply: *mut RedisModuleCallReply) -> Option<f64> {
+    unsafe { RedisModule_CallReplyDouble.map(|call_reply_double| call_reply_double(reply)) }
+}
+
+// Redis 7.
+pub fn call_reply_bool(reply: *mut RedisModuleCallReply) -> Option<c_int> {
+    unsafe { RedisModule_CallReplyBool.map(|call_reply_bool| call_reply_bool(reply)) }
+}
+
+// The INFO API, Redis 6. The `InfoAdd*` functions are only called back from
+// a registered info function, so they're always there then.
+pub fn register_info_func(ctx: *mut RedisModuleCtx, cb: RedisModuleInfoFunc) -> Option<Status> {
+    unsafe { RedisModule_RegisterInfoFunc.map(|register| register(ctx, cb)) }
+}
+
+pub fn info_add_section(ctx: *mut RedisModuleInfoCtx, name: *const c_char) -> Option<Status> {
+    unsafe { RedisModule_InfoAddSection.map(|add_section| add_section(ctx, name)) }
+}
+
+pub fn info_add_field_long_long(
+    ctx: *mut RedisModuleInfoCtx,
+    field: *const c_char,
+    value: c_longlong,
+) -> Option<Status> {
+    unsafe { RedisModule_InfoAddFieldLongLong.map(|add_field| add_field(ctx, field, value)) }
+}
+
+pub fn info_add_field_cstring(
+    ctx: *mut RedisModuleInfoCtx,
+    field: *const c_char,
+    value: *const c_char,
+) -> Option<Status> {
+    unsafe { RedisModule_InfoAddFieldCString.map(|add_field| add_field(ctx, field, value)) }
+}
+
+pub fn rm_alloc(size: size_t) -> *mut u8 {
+    unsafe { RedisModule_Alloc(size) as *mut u8 }
+}
+
+pub fn rm_free(ptr: *mut u8) {
+    unsafe { RedisModule_Free(ptr as *mut c_void) }
+}
+
+pub fn auto_memory(ctx: *mut RedisModuleCtx) {
+    unsafe { RedisModule_AutoMemory(ctx) }
+}
+
+//extern function of C
+#[allow(improper_ctypes)]
+#[link(name = "redis_mod_callable", kind = "static")]
+extern "C" {
+    pub fn RedisModuleCallable2_ReplyInteger(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        key: *const i8,
+        arg0: *const i8,
+    ) -> c_longlong;
+
+    pub fn RedisModule_Call1(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        key: *const i8
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModule_Call2(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        key: *const i8,
+        arg0: *const i8
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModule_Call3(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        key: *const i8,
+        arg0: *const i8,
+        arg1: *const i8
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModule_CallArgv(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        argv: *mut *mut RedisModuleString,
+        argc: size_t
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModule_CallArgvReplicate(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        argv: *mut *mut RedisModuleString,
+        argc: size_t
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModule_ReplicateArgv(
+        ctx: *mut RedisModuleCtx,
+        cmdname: *const i8,
+        argv: *mut *mut RedisModuleString,
+        argc: size_t
+    ) -> Status;
+
+    pub fn RedisModule_EmitAOFArgv(
+        io: *mut RedisModuleIO,
+        cmdname: *const i8,
+        argv: *mut *mut RedisModuleString,
+        argc: size_t
+    );
+
+    pub fn RedisModule_CallKeys(
+        ctx: *mut RedisModuleCtx,
+        arg0: *const i8
+    ) -> *mut RedisModuleCallReply;
+
+    pub fn RedisModuleHash_Get(
+        key: *mut RedisModuleKey,
+        field: *mut RedisModuleString
+    ) -> *mut RedisModuleString;
+
+    pub fn RedisModuleHash_MGet(
+        key: *mut RedisModuleKey,
+        fields: *mut *mut RedisModuleString,
+        values: *mut *mut RedisModuleString,
+        len: size_t
+    );
 
     pub fn RedisModuleHash_Set(
         key: *mut RedisModuleKey,
@@ -364,6 +1472,12 @@ extern "C" {
         val: *mut RedisModuleString
     ) -> Status;
 
+    pub fn RedisModuleHash_Del(
+        key: *mut RedisModuleKey,
+        flags: HashFlags,
+        field: *mut RedisModuleString
+    ) -> c_int;
+
 }
 
 
@@ -439,20 +1553,29 @@ extern "C" {
             mode: KeyMode
         ) -> *const u8;
 
+    static RedisModule_StringTruncate:
+        extern "C" fn(key: *mut RedisModuleKey, newlen: size_t) -> Status;
+
     static RedisModule_DeleteKey:
         extern "C" fn(key: *mut RedisModuleKey) -> Status;
 
+    static RedisModule_UnlinkKey:
+        extern "C" fn(key: *mut RedisModuleKey) -> Status;
+
     static RedisModule_ReplyWithArray:
         extern "C" fn(
             ctx: *mut RedisModuleCtx,
             len: c_long
         ) -> Status;
 
+    static RedisModule_ReplySetArrayLength:
+        extern "C" fn(ctx: *mut RedisModuleCtx, len: c_long);
+
     static RedisModule_ReplyWithError:
         extern "C" fn(
             ctx: *mut RedisModuleCtx,
             err: *const u8
-        );
+        ) -> Status;
 
     static RedisModule_ReplyWithLongLong:
         extern "C" fn(
@@ -477,6 +1600,26 @@ extern "C" {
             ctx: *mut RedisModuleCtx
     );
 
+    static RedisModule_ReplyWithNullArray:
+        Option<extern "C" fn(
+            ctx: *mut RedisModuleCtx
+    ) -> Status>;
+
+    static RedisModule_ReplyWithStringBuffer:
+        extern "C" fn(ctx: *mut RedisModuleCtx, buf: *const c_char, len: size_t) -> Status;
+
+    static RedisModule_ReplyWithDouble:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            d: f64
+    ) -> Status;
+
+    static RedisModule_ReplyWithCallReply:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            reply: *mut RedisModuleCallReply
+    ) -> Status;
+
     static RedisModule_CreateString:
         extern "C" fn(ctx: *mut RedisModuleCtx, ptr: *const u8, len: size_t)
             -> *mut RedisModuleString;
@@ -485,14 +1628,55 @@ extern "C" {
         extern "C" fn(ctx: *mut RedisModuleCtx, str: *mut RedisModuleString);
 
     static RedisModule_Log:
-        extern "C" fn(ctx: *mut RedisModuleCtx, level: *const u8, fmt: *const u8);
+        extern "C" fn(ctx: *mut RedisModuleCtx, level: *const u8, fmt: *const u8, ...);
 
     static RedisModule_SetExpire:
         extern "C" fn(key: *mut RedisModuleKey, expire: c_longlong) -> Status;
 
+    static RedisModule_GetExpire:
+        extern "C" fn(key: *mut RedisModuleKey) -> c_longlong;
+
     static RedisModule_StringPtrLen:
         extern "C" fn(str: *mut RedisModuleString, len: *mut size_t) -> *const u8;
 
+    static RedisModule_StringCompare:
+        extern "C" fn(a: *mut RedisModuleString, b: *mut RedisModuleString) -> c_int;
+
+    static RedisModule_ZsetAdd:
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            score: f64,
+            ele: *mut RedisModuleString,
+            flagsptr: *mut c_int
+        ) -> Status;
+
+    static RedisModule_ZsetScore:
+        extern "C" fn(key: *mut RedisModuleKey, ele: *mut RedisModuleString, score: *mut f64) -> Status;
+
+    static RedisModule_ZsetFirstInScoreRange:
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            min: f64,
+            max: f64,
+            minex: c_int,
+            maxex: c_int
+        ) -> Status;
+
+    static RedisModule_ZsetRangeCurrentElement:
+        extern "C" fn(key: *mut RedisModuleKey, score: *mut f64) -> *mut RedisModuleString;
+
+    static RedisModule_ZsetRangeNext:
+        extern "C" fn(key: *mut RedisModuleKey) -> c_int;
+
+    static RedisModule_ZsetRangeEndReached:
+        extern "C" fn(key: *mut RedisModuleKey) -> c_int;
+
+    static RedisModule_ZsetRangeStop:
+        extern "C" fn(key: *mut RedisModuleKey);
+
+    static RedisModule_ValueLength:
+        extern "C" fn(key: *mut RedisModuleKey) -> size_t;
+
     static RedisModule_ListPush:
         extern "C" fn(key: *mut RedisModuleKey, place: c_int, ele: *mut RedisModuleString) -> Status;
 
@@ -511,5 +1695,353 @@ extern "C" {
     static RedisModule_AutoMemory:
         extern "C" fn(ctx: *mut RedisModuleCtx);
 
+    static RedisModule_BlockClient:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            reply_callback: Option<RedisModuleCmdFunc>,
+            timeout_callback: Option<RedisModuleCmdFunc>,
+            free_privdata: Option<RedisModuleFreePrivDataFunc>,
+            timeout_ms: c_longlong
+        ) -> *mut RedisModuleBlockedClient;
+
+    static RedisModule_UnblockClient:
+        extern "C" fn(bc: *mut RedisModuleBlockedClient, privdata: *mut c_void) -> Status;
+
+    static RedisModule_GetBlockedClientPrivateData:
+        extern "C" fn(ctx: *mut RedisModuleCtx) -> *mut c_void;
+
+    static RedisModule_CreateTimer:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            period: c_longlong,
+            callback: Option<RedisModuleTimerProc>,
+            data: *mut c_void
+        ) -> RedisModuleTimerID;
+
+    static RedisModule_StopTimer:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            id: RedisModuleTimerID,
+            data: *mut *mut c_void
+        ) -> Status;
+
+    static RedisModule_RegisterClusterMessageReceiver:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            msg_type: u8,
+            callback: Option<RedisModuleClusterMessageReceiver>
+        );
+
+    static RedisModule_SendClusterMessage:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            target_id: *const c_char,
+            msg_type: u8,
+            msg: *const u8,
+            len: u32
+        ) -> Status;
+
+    static RedisModule_GetMyClusterID:
+        extern "C" fn() -> *const c_char;
+
+    static RedisModule_GetClusterSize:
+        extern "C" fn() -> size_t;
+
+    static RedisModule_GetContextFlags:
+        extern "C" fn(ctx: *mut RedisModuleCtx) -> c_int;
+
+    static RedisModule_Milliseconds:
+        extern "C" fn() -> c_longlong;
+
+    static RedisModule_GetClientId:
+        extern "C" fn(ctx: *mut RedisModuleCtx) -> c_ulonglong;
+
+    static RedisModule_GetSelectedDb:
+        extern "C" fn(ctx: *mut RedisModuleCtx) -> c_int;
+
+    static RedisModule_SelectDb:
+        extern "C" fn(ctx: *mut RedisModuleCtx, newid: c_int) -> Status;
+
+    static RedisModule_GetThreadSafeContext:
+        extern "C" fn(bc: *mut RedisModuleBlockedClient) -> *mut RedisModuleCtx;
+
+    static RedisModule_GetDetachedThreadSafeContext:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx) -> *mut RedisModuleCtx>;
+
+    static RedisModule_FreeThreadSafeContext:
+        extern "C" fn(ctx: *mut RedisModuleCtx);
+
+    static RedisModule_ThreadSafeContextLock:
+        extern "C" fn(ctx: *mut RedisModuleCtx);
+
+    static RedisModule_ThreadSafeContextUnlock:
+        extern "C" fn(ctx: *mut RedisModuleCtx);
+
+    static RedisModule_CreateDataType:
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            name: *const u8,
+            encver: c_int,
+            typemethods: *mut RedisModuleTypeMethods
+        ) -> *mut RedisModuleType;
+
+    static RedisModule_ModuleTypeSetValue:
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            mt: *mut RedisModuleType,
+            value: *mut c_void
+        ) -> Status;
+
+    static RedisModule_ModuleTypeGetType:
+        extern "C" fn(key: *mut RedisModuleKey) -> *mut RedisModuleType;
+
+    static RedisModule_ModuleTypeGetValue:
+        extern "C" fn(key: *mut RedisModuleKey) -> *mut c_void;
+
+    static RedisModule_SaveUnsigned:
+        extern "C" fn(io: *mut RedisModuleIO, value: u64);
+
+    static RedisModule_LoadUnsigned:
+        extern "C" fn(io: *mut RedisModuleIO) -> u64;
+
+    static RedisModule_SaveSigned:
+        extern "C" fn(io: *mut RedisModuleIO, value: i64);
+
+    static RedisModule_LoadSigned:
+        extern "C" fn(io: *mut RedisModuleIO) -> i64;
+
+    static RedisModule_SaveStringBuffer:
+        extern "C" fn(io: *mut RedisModuleIO, str: *const u8, len: size_t);
+
+    static RedisModule_LoadStringBuffer:
+        extern "C" fn(io: *mut RedisModuleIO, len: *mut size_t) -> *mut u8;
+
+    static RedisModule_SaveDouble:
+        extern "C" fn(io: *mut RedisModuleIO, value: f64);
+
+    static RedisModule_LoadDouble:
+        extern "C" fn(io: *mut RedisModuleIO) -> f64;
+
+    static RedisModule_DigestAddStringBuffer:
+        extern "C" fn(md: *mut RedisModuleDigest, ele: *const u8, len: size_t);
+
+    static RedisModule_DigestAddLongLong:
+        extern "C" fn(md: *mut RedisModuleDigest, ele: c_longlong);
+
+    static RedisModule_DigestEndSequence:
+        extern "C" fn(md: *mut RedisModuleDigest);
+
+    static RedisModule_GetCommand:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, name: *const c_char) -> *mut RedisModuleCommand>;
+
+    static RedisModule_CreateSubcommand: Option<
+        extern "C" fn(
+            parent: *mut RedisModuleCommand,
+            name: *const u8,
+            cmdfunc: Option<RedisModuleCmdFunc>,
+            strflags: *const u8,
+            firstkey: c_int,
+            lastkey: c_int,
+            keystep: c_int,
+        ) -> Status,
+    >;
+
+    static RedisModule_SetCommandInfo:
+        Option<extern "C" fn(command: *mut RedisModuleCommand, info: *const RedisModuleCommandInfo) -> Status>;
+
+    static RedisModule_GetCurrentCommandName:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx) -> *const c_char>;
+
+    static RedisModule_ClusterKeySlot:
+        Option<extern "C" fn(key: *mut RedisModuleString) -> c_uint>;
+
+    static RedisModule_ReplyWithMap:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, len: c_long) -> Status>;
+
+    static RedisModule_ReplyWithSet:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, len: c_long) -> Status>;
+
+    static RedisModule_ReplyWithBool:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, b: c_int) -> Status>;
+
+    static RedisModule_ReplyWithBigNumber:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, bignum: *const c_char, len: size_t) -> Status>;
+
+    static RedisModule_ReplyWithVerbatimStringType:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, buf: *const c_char, len: size_t, ext: *const c_char) -> Status>;
+
+    static RedisModule_CallReplyDouble:
+        Option<extern "C" fn(reply: *mut RedisModuleCallReply) -> f64>;
+
+    static RedisModule_CallReplyBool:
+        Option<extern "C" fn(reply: *mut RedisModuleCallReply) -> c_int>;
+
+    static RedisModule_ListGet:
+        Option<extern "C" fn(key: *mut RedisModuleKey, index: c_long) -> *mut RedisModuleString>;
+
+    static RedisModule_ListSet:
+        Option<extern "C" fn(key: *mut RedisModuleKey, index: c_long, value: *mut RedisModuleString) -> Status>;
+
+    static RedisModule_ListInsert:
+        Option<extern "C" fn(key: *mut RedisModuleKey, index: c_long, value: *mut RedisModuleString) -> Status>;
+
+    static RedisModule_StreamAdd: Option<
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            flags: c_int,
+            id: *mut RedisModuleStreamID,
+            argv: *mut *mut RedisModuleString,
+            numfields: i64,
+        ) -> Status,
+    >;
+
+    static RedisModule_StreamIteratorStart: Option<
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            flags: c_int,
+            startid: *mut RedisModuleStreamID,
+            endid: *mut RedisModuleStreamID,
+        ) -> Status,
+    >;
+
+    static RedisModule_StreamIteratorStop:
+        Option<extern "C" fn(key: *mut RedisModuleKey) -> Status>;
+
+    static RedisModule_StreamIteratorNextID:
+        Option<extern "C" fn(key: *mut RedisModuleKey, id: *mut RedisModuleStreamID, numfields: *mut c_long) -> Status>;
+
+    static RedisModule_StreamIteratorNextField: Option<
+        extern "C" fn(
+            key: *mut RedisModuleKey,
+            field_ptr: *mut *mut RedisModuleString,
+            value_ptr: *mut *mut RedisModuleString,
+        ) -> Status,
+    >;
+
+    static RedisModule_RegisterCommandFilter: Option<
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            cb: RedisModuleCommandFilterFunc,
+            flags: c_int,
+        ) -> *mut RedisModuleCommandFilter,
+    >;
+
+    static RedisModule_CommandFilterArgsCount:
+        Option<extern "C" fn(fctx: *mut RedisModuleCommandFilterCtx) -> c_int>;
+
+    static RedisModule_CommandFilterArgGet:
+        Option<extern "C" fn(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int) -> *mut RedisModuleString>;
+
+    static RedisModule_CommandFilterArgInsert:
+        Option<extern "C" fn(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int, arg: *mut RedisModuleString) -> Status>;
+
+    static RedisModule_CommandFilterArgReplace:
+        Option<extern "C" fn(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int, arg: *mut RedisModuleString) -> Status>;
+
+    static RedisModule_CommandFilterArgDelete:
+        Option<extern "C" fn(fctx: *mut RedisModuleCommandFilterCtx, pos: c_int) -> Status>;
+
+    static RedisModule_NotifyKeyspaceEvent:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, types: c_int, event: *const c_char, key: *mut RedisModuleString) -> Status>;
+
+    static RedisModule_MonotonicMicroseconds:
+        Option<extern "C" fn() -> u64>;
+
+    static RedisModule_GetLRU:
+        Option<extern "C" fn(key: *mut RedisModuleKey, lru_idle: *mut c_longlong) -> Status>;
+
+    static RedisModule_GetLFU:
+        Option<extern "C" fn(key: *mut RedisModuleKey, lfu_freq: *mut c_longlong) -> Status>;
+
+    static RedisModule_DbSize:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx) -> c_ulonglong>;
+
+    static RedisModule_RandomKey:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx) -> *mut RedisModuleString>;
+
+    static RedisModule_GetClientInfoById:
+        Option<extern "C" fn(ci: *mut c_void, id: u64) -> Status>;
+
+    static RedisModule_GetClientNameById:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, id: u64) -> *mut RedisModuleString>;
+
+    static RedisModule_RegisterInfoFunc:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx, cb: RedisModuleInfoFunc) -> Status>;
+
+    static RedisModule_InfoAddSection:
+        Option<extern "C" fn(ctx: *mut RedisModuleInfoCtx, name: *const c_char) -> Status>;
+
+    static RedisModule_InfoAddFieldLongLong:
+        Option<extern "C" fn(ctx: *mut RedisModuleInfoCtx, field: *const c_char, value: c_longlong) -> Status>;
+
+    static RedisModule_InfoAddFieldCString:
+        Option<extern "C" fn(ctx: *mut RedisModuleInfoCtx, field: *const c_char, value: *const c_char) -> Status>;
+
+    static RedisModule_RegisterBoolConfig: Option<
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            name: *const c_char,
+            default_val: c_int,
+            flags: c_uint,
+            getfn: RedisModuleConfigGetBoolFunc,
+            setfn: RedisModuleConfigSetBoolFunc,
+            applyfn: Option<RedisModuleConfigApplyFunc>,
+            privdata: *mut c_void,
+        ) -> Status,
+    >;
+
+    static RedisModule_RegisterStringConfig: Option<
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            name: *const c_char,
+            default_val: *const c_char,
+            flags: c_uint,
+            getfn: RedisModuleConfigGetStringFunc,
+            setfn: RedisModuleConfigSetStringFunc,
+            applyfn: Option<RedisModuleConfigApplyFunc>,
+            privdata: *mut c_void,
+        ) -> Status,
+    >;
+
+    static RedisModule_RegisterNumericConfig: Option<
+        extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            name: *const c_char,
+            default_val: c_longlong,
+            flags: c_uint,
+            min: c_longlong,
+            max: c_longlong,
+            getfn: RedisModuleConfigGetNumericFunc,
+            setfn: RedisModuleConfigSetNumericFunc,
+            applyfn: Option<RedisModuleConfigApplyFunc>,
+            privdata: *mut c_void,
+        ) -> Status,
+    >;
+
+    static RedisModule_LoadConfigs:
+        Option<extern "C" fn(ctx: *mut RedisModuleCtx) -> Status>;
+
+    static RedisModule_ScanCursorCreate:
+        Option<extern "C" fn() -> *mut RedisModuleScanCursor>;
+
+    static RedisModule_ScanCursorDestroy:
+        Option<extern "C" fn(cursor: *mut RedisModuleScanCursor)>;
+
+    static RedisModule_Scan:
+        Option<extern "C" fn(
+            ctx: *mut RedisModuleCtx,
+            cursor: *mut RedisModuleScanCursor,
+            callback: Option<RedisModuleScanCB>,
+            privdata: *mut c_void
+        ) -> c_int>;
+
+    static RedisModule_ScanKey:
+        Option<extern "C" fn(
+            key: *mut RedisModuleKey,
+            cursor: *mut RedisModuleScanCursor,
+            callback: Option<RedisModuleScanKeyCB>,
+            privdata: *mut c_void
+        ) -> c_int>;
+
 }
 
@@ -0,0 +1,408 @@
+use crate::error::RModError;
+use crate::redis::{manifest_redis_string, raw, Redis};
+use std::fmt;
+use std::os::raw::c_void;
+
+/// `ScanCursor` is the position of a resumable `SCAN`-style iteration.
+///
+/// A module command exposing its own scan (e.g. `MYMOD.SCAN cursor`) parses
+/// the cursor given by the client, iterates a bounded number of elements, and
+/// hands the next cursor back. A client starts at `0` and stops once it is
+/// given `0` again, which is the same contract as the native `SCAN`/`HSCAN`.
+///
+/// Batches are fetched by calling `SCAN`/`HSCAN` rather than through
+/// `RedisModule_Scan`, since the module API's cursor is opaque and can't be
+/// handed to a client to resume from. `Redis::scan_each` uses the module API
+/// for iterations that finish within a single command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScanCursor(u64);
+
+impl ScanCursor {
+    /// Returns the cursor a client starts an iteration with.
+    pub fn start() -> ScanCursor {
+        ScanCursor(0)
+    }
+
+    /// Parses a cursor as it was sent by a client.
+    pub fn parse(cursor: &str) -> Result<ScanCursor, RModError> {
+        Ok(ScanCursor(cursor.parse::<u64>()?))
+    }
+
+    /// Detects whether the iteration has come back around to its end.
+    pub fn is_done(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for ScanCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `PageToken` is the opaque continuation token handed to clients along with
+/// a page of results, so that they can request the next page.
+///
+/// It encodes either a `ScanCursor` or a plain offset into a result set, and
+/// clients should only ever send it back verbatim. Like a cursor, `0` starts
+/// a new iteration and comes back once there are no more pages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageToken(u64);
+
+impl PageToken {
+    /// Returns the token a client starts paging with.
+    pub fn start() -> PageToken {
+        PageToken(0)
+    }
+
+    pub fn from_cursor(cursor: ScanCursor) -> PageToken {
+        PageToken(cursor.0)
+    }
+
+    pub fn from_offset(offset: usize) -> PageToken {
+        PageToken(offset as u64)
+    }
+
+    /// Returns the position encoded in the token as a scan cursor.
+    pub fn cursor(&self) -> ScanCursor {
+        ScanCursor(self.0)
+    }
+
+    /// Returns the position encoded in the token as an offset.
+    pub fn offset(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// Encodes the token in the form that's sent to clients.
+    pub fn encode(&self) -> String {
+        if self.0 == 0 {
+            return String::from("0");
+        }
+        format!("{}{:x}", PAGE_TOKEN_PREFIX, self.0)
+    }
+
+    /// Decodes a token as it was sent by a client.
+    pub fn decode(token: &str) -> Result<PageToken, RModError> {
+        if token == "0" {
+            return Ok(PageToken::start());
+        }
+        if !token.starts_with(PAGE_TOKEN_PREFIX) {
+            return Err(error!("Invalid continuation token"));
+        }
+        u64::from_str_radix(&token[PAGE_TOKEN_PREFIX.len()..], 16)
+            .map(PageToken)
+            .map_err(|_| error!("Invalid continuation token"))
+    }
+}
+
+// Distinguishes tokens from raw cursors or offsets that clients might be
+// tempted to build by hand.
+const PAGE_TOKEN_PREFIX: &str = "pg";
+
+/// `PaginatedReply` is a page of a larger result set along with the token
+/// needed to fetch the page after it, if there's one.
+///
+/// It's replied as `[token, [items...]]`, the same shape as `SCAN`, so that
+/// all of a module's paginated commands look alike to clients.
+pub struct PaginatedReply {
+    items: Vec<String>,
+    next:  Option<PageToken>,
+}
+
+impl PaginatedReply {
+    pub fn new(items: Vec<String>, next: Option<PageToken>) -> PaginatedReply {
+        PaginatedReply { items, next }
+    }
+
+    /// Builds a page from one batch of a scan, which is the last page once
+    /// the cursor is done.
+    pub fn from_scan(cursor: ScanCursor, items: Vec<String>) -> PaginatedReply {
+        let next = if cursor.is_done() {
+            None
+        } else {
+            Some(PageToken::from_cursor(cursor))
+        };
+        PaginatedReply { items, next }
+    }
+
+    pub fn items(&self) -> &[String] {
+        self.items.as_slice()
+    }
+
+    pub fn next(&self) -> Option<PageToken> {
+        self.next
+    }
+}
+
+impl Redis {
+    /// Iterates one batch of the keyspace starting at `cursor`, returning the
+    /// next cursor and the keys found.
+    ///
+    /// `count` is a hint for the amount of work to do per call, as with the
+    /// `COUNT` option of `SCAN`, so fewer (or more) keys may come back.
+    pub fn scan(
+        &self,
+        cursor: ScanCursor,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> Result<(ScanCursor, Vec<String>), RModError> {
+        let cursor = cursor.to_string();
+        self.scan_command("scan", &[cursor.as_str()], pattern, count)
+    }
+
+    /// Calls `f` with the name of every key in the selected database matching
+    /// `pattern`, a glob-style pattern as with the `MATCH` option of `SCAN`.
+    ///
+    /// Unlike `call_keys`, which runs `KEYS`, this walks the keyspace with a
+    /// cursor so that it's safe on large databases. As with `SCAN`, a key
+    /// may be given more than once if the keyspace is resized meanwhile, and
+    /// `f` mustn't add or delete keys. Requires Redis 6 or later.
+    pub fn scan_each<F>(&self, pattern: Option<&str>, mut f: F) -> Result<(), RModError>
+    where
+        F: FnMut(&str),
+    {
+        let cursor = raw::scan_cursor_create()
+            .ok_or_else(|| error!("Scanning the keyspace requires Redis 6 or later"))?;
+        let mut state = ScanState {
+            pattern: pattern.map(str::as_bytes),
+            f:       &mut f,
+            error:   None,
+        };
+        while raw::scan(
+            self.ctx,
+            cursor,
+            Some(scan_callback),
+            &mut state as *mut ScanState as *mut c_void,
+        ) != 0
+            && state.error.is_none()
+        {}
+        raw::scan_cursor_destroy(cursor);
+
+        match state.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the names of all keys in the selected database matching
+    /// `pattern`, walking the keyspace like `scan_each` does.
+    pub fn scan_all(&self, pattern: Option<&str>) -> Result<Vec<String>, RModError> {
+        let mut keys = Vec::new();
+        self.scan_each(pattern, |key| keys.push(key.to_string()))?;
+        Ok(keys)
+    }
+
+    /// Iterates one batch of the fields of a hash starting at `cursor`,
+    /// returning the next cursor and the matching field/value pairs.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: ScanCursor,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> Result<(ScanCursor, Vec<(String, String)>), RModError> {
+        let cursor = cursor.to_string();
+        let (next, elements) =
+            self.scan_command("hscan", &[key, cursor.as_str()], pattern, count)?;
+        let mut pairs = Vec::with_capacity(elements.len() / 2);
+        let mut iter = elements.into_iter();
+        while let (Some(field), Some(value)) = (iter.next(), iter.next()) {
+            pairs.push((field, value));
+        }
+        Ok((next, pairs))
+    }
+
+    /// Replies with the `[cursor, [elements...]]` shape that clients expect
+    /// back from a `SCAN`-style command.
+    pub fn reply_scan(&self, cursor: ScanCursor, elements: &[String]) -> Result<(), RModError> {
+        self.reply_array(2)?;
+        self.reply_string(cursor.to_string().as_str())?;
+        self.reply_array(elements.len() as i64)?;
+        for ele in elements {
+            self.reply_string(ele)?;
+        }
+        Ok(())
+    }
+
+    /// Replies with a page of results and its continuation token. The token
+    /// is `0` when it's the last page.
+    pub fn reply_paginated(&self, page: &PaginatedReply) -> Result<(), RModError> {
+        let token = page.next.unwrap_or_else(PageToken::start).encode();
+        self.reply_array(2)?;
+        self.reply_string(token.as_str())?;
+        self.reply_array(page.items.len() as i64)?;
+        for item in &page.items {
+            self.reply_string(item)?;
+        }
+        Ok(())
+    }
+
+    fn scan_command(
+        &self,
+        cmdname: &str,
+        leading_args: &[&str],
+        pattern: Option<&str>,
+        count: usize,
+    ) -> Result<(ScanCursor, Vec<String>), RModError> {
+        let count = count.to_string();
+        let mut args: Vec<&str> = leading_args.to_vec();
+        if let Some(pattern) = pattern {
+            args.push("match");
+            args.push(pattern);
+        }
+        args.push("count");
+        args.push(count.as_str());
+
+        let reply = self.call(cmdname, args.as_slice())?;
+        let next = ScanCursor::parse(reply.reply_array_element(0)?.to_string()?.as_str())?;
+        let elements_reply = reply.reply_array_element(1)?;
+        let size = elements_reply.check_length();
+        let mut elements: Vec<String> = Vec::with_capacity(size);
+        for idx in 0..size {
+            elements.push(elements_reply.reply_array_element(idx)?.to_string()?);
+        }
+        Ok((next, elements))
+    }
+}
+
+// Carries the callback of `scan_each` through `RedisModule_Scan`, along with
+// the first error met since the callback itself can't return one.
+struct ScanState<'a> {
+    pattern: Option<&'a [u8]>,
+    f:       &'a mut dyn FnMut(&str),
+    error:   Option<RModError>,
+}
+
+extern "C" fn scan_callback(
+    _ctx: *mut raw::RedisModuleCtx,
+    keyname: *mut raw::RedisModuleString,
+    _key: *mut raw::RedisModuleKey,
+    privdata: *mut c_void,
+) {
+    let state = unsafe { &mut *(privdata as *mut ScanState) };
+    if state.error.is_some() {
+        return;
+    }
+    match manifest_redis_string(keyname) {
+        Ok(name) => {
+            // `Option::is_none_or` would need a much newer Rust than the crate's.
+            #[allow(clippy::unnecessary_map_or)]
+            if state.pattern.map_or(true, |pattern| glob_match(pattern, name.as_bytes())) {
+                (state.f)(&name);
+            }
+        }
+        Err(err) => state.error = Some(err.into()),
+    }
+}
+
+// Matches `s` against a glob-style pattern supporting `*`, `?`, `[...]`
+// (with ranges and `^` negation) and `\` escapes, like `SCAN ... MATCH`.
+//
+// Like Redis's `stringmatchlen`, a `*` only ever backtracks to the most recent
+// one, which keeps matching linear in the pattern times the string.
+fn glob_match(pattern: &[u8], s: &[u8]) -> bool {
+    let (mut p, mut i) = (pattern, 0);
+    // The pattern after the last `*` and where in `s` it's matched from.
+    let mut star: Option<(&[u8], usize)> = None;
+    loop {
+        if let Some((b'*', rest)) = p.split_first() {
+            p = rest;
+            star = Some((rest, i));
+            continue;
+        }
+        if i < s.len() {
+            if let Some(rest) = match_one(p, s[i]) {
+                p = rest;
+                i += 1;
+                continue;
+            }
+        } else if p.is_empty() {
+            return true;
+        }
+        match star {
+            Some((rest, start)) if start < s.len() => {
+                star = Some((rest, start + 1));
+                p = rest;
+                i = start + 1;
+            }
+            _ => return false,
+        }
+    }
+}
+
+// Matches `c` against the first element of `pattern`, which isn't a `*`,
+// returning the rest of the pattern if it matched.
+fn match_one(pattern: &[u8], c: u8) -> Option<&[u8]> {
+    match pattern.split_first()? {
+        (b'?', rest) => Some(rest),
+        (b'[', rest) => match match_class(rest, c) {
+            Some((matched, after)) => Some(after).filter(|_| matched),
+            // An unterminated class is matched as a literal `[`.
+            None => Some(rest).filter(|_| c == b'['),
+        },
+        (b'\\', [lit, rest @ ..]) => Some(rest).filter(|_| *lit == c),
+        (&p, rest) => Some(rest).filter(|_| p == c),
+    }
+}
+
+// Matches `c` against the class that `pattern` starts with (just after its
+// `[`), returning whether it matched and the rest of the pattern after `]`.
+fn match_class(pattern: &[u8], c: u8) -> Option<(bool, &[u8])> {
+    let (negate, mut pattern) = match pattern.split_first() {
+        Some((b'^', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+    let mut matched = false;
+    loop {
+        match pattern {
+            [] => return None,
+            [b']', rest @ ..] => return Some((matched != negate, rest)),
+            [b'\\', lit, rest @ ..] => {
+                matched |= *lit == c;
+                pattern = rest;
+            }
+            [lo, b'-', hi, rest @ ..] if *hi != b']' => {
+                let (lo, hi) = if lo <= hi { (*lo, *hi) } else { (*hi, *lo) };
+                matched |= lo <= c && c <= hi;
+                pattern = rest;
+            }
+            [lit, rest @ ..] => {
+                matched |= *lit == c;
+                pattern = rest;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob_match_patterns() {
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"user:*", b"user:1"));
+        assert!(!glob_match(b"user:*", b"item:1"));
+        assert!(glob_match(b"h?llo", b"hallo"));
+        assert!(!glob_match(b"h?llo", b"hllo"));
+        assert!(glob_match(b"h[ae]llo", b"hello"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-c]llo", b"hbllo"));
+        assert!(glob_match(b"a\\*b", b"a*b"));
+        assert!(!glob_match(b"a\\*b", b"axb"));
+        assert!(glob_match(b"a[b", b"a[b"));
+        assert!(glob_match(b"*a*b*c", b"xxaxxbxxc"));
+        assert!(!glob_match(b"*a*b*c", b"xxaxxbxx"));
+    }
+
+    #[test]
+    fn glob_match_many_stars_is_not_exponential() {
+        let pattern = b"*a".repeat(30);
+        let s = vec![b'a'; 60];
+        assert!(glob_match(&pattern, &s));
+        assert!(!glob_match(&pattern, &[b'a'; 29]));
+        let mut s = vec![b'a'; 100];
+        s.push(b'b');
+        assert!(!glob_match(&pattern, &s));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::RModError;
+use crate::redis::{raw, BlockedClient, Redis, RedisCallReply};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ptr;
+
+/// `ThreadSafeContext` is a context that can be used from a thread other than
+/// the main Redis thread, as long as it's locked while in use.
+///
+/// Its primary function is to ensure that the context is freed when it goes
+/// out of scope, and that it's only ever handed out as a `Redis` while holding
+/// the Redis global lock.
+pub struct ThreadSafeContext {
+    ctx: *mut raw::RedisModuleCtx,
+}
+
+// The context is only usable through `lock`, which serializes access to it
+// with the Redis main thread.
+unsafe impl Send for ThreadSafeContext {}
+
+impl ThreadSafeContext {
+    /// Creates a context that isn't bound to any client.
+    pub fn new() -> ThreadSafeContext {
+        ThreadSafeContext {
+            ctx: raw::get_thread_safe_context(ptr::null_mut()),
+        }
+    }
+
+    /// Creates a context bound to a blocked client. Replies made through it
+    /// are accumulated and sent to the client once it's unblocked.
+    pub fn with_blocked_client(bc: &BlockedClient) -> ThreadSafeContext {
+        ThreadSafeContext {
+            ctx: raw::get_thread_safe_context(bc.inner),
+        }
+    }
+
+    /// Acquires the Redis global lock, blocking until the main thread lets go
+    /// of it. The lock is held until the returned guard is dropped or
+    /// `unlock`ed.
+    pub fn lock(&self) -> ThreadSafeContextGuard<'_> {
+        raw::thread_safe_context_lock(self.ctx);
+        ThreadSafeContextGuard {
+            redis: Redis::new(self.ctx),
+            _ctx: PhantomData,
+        }
+    }
+}
+
+impl Default for ThreadSafeContext {
+    fn default() -> ThreadSafeContext {
+        ThreadSafeContext::new()
+    }
+}
+
+impl Drop for ThreadSafeContext {
+    // Frees resources appropriately as a ThreadSafeContext goes out of scope.
+    fn drop(&mut self) {
+        raw::free_thread_safe_context(self.ctx);
+    }
+}
+
+/// `ThreadSafeContextGuard` gives access to Redis while holding the global
+/// lock, and releases the lock when it goes out of scope.
+pub struct ThreadSafeContextGuard<'a> {
+    redis: Redis,
+    _ctx:  PhantomData<&'a ThreadSafeContext>,
+}
+
+impl<'a> ThreadSafeContextGuard<'a> {
+    /// Releases the lock.
+    pub fn unlock(self) {}
+
+    /// Calls a Redis command. The reply borrows the guard, since it can only
+    /// be read (and freed) while the lock is held.
+    pub fn call(&self, cmdname: &str, args: &[&str]) -> Result<LockedCallReply<'_>, RModError> {
+        Ok(LockedCallReply {
+            reply:  self.redis.call(cmdname, args)?,
+            _guard: PhantomData,
+        })
+    }
+}
+
+/// `LockedCallReply` is the reply of a command called through a
+/// `ThreadSafeContextGuard`, which it can't outlive.
+pub struct LockedCallReply<'g> {
+    reply:  RedisCallReply,
+    _guard: PhantomData<&'g ()>,
+}
+
+impl<'g> Deref for LockedCallReply<'g> {
+    type Target = RedisCallReply;
+
+    fn deref(&self) -> &RedisCallReply {
+        &self.reply
+    }
+}
+
+impl<'a> Deref for ThreadSafeContextGuard<'a> {
+    type Target = Redis;
+
+    fn deref(&self) -> &Redis {
+        &self.redis
+    }
+}
+
+impl<'a> Drop for ThreadSafeContextGuard<'a> {
+    fn drop(&mut self) {
+        raw::thread_safe_context_unlock(self.redis.ctx);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::RModError;
+use crate::redis::{raw, Redis};
+use std::os::raw::c_void;
+use std::ptr;
+use time;
+
+type TimerCallback = Box<dyn FnOnce(&Redis)>;
+
+/// `TimerId` identifies a timer created with `Redis::create_timer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimerId(raw::RedisModuleTimerID);
+
+impl Redis {
+    /// Schedules `callback` to run once on the main thread after `period`
+    /// has elapsed.
+    ///
+    /// The callback is given its own context, so it can't use anything tied to
+    /// the context of the command that created the timer (like open keys).
+    pub fn create_timer<F>(&self, period: time::Duration, callback: F) -> TimerId
+    where
+        F: FnOnce(&Redis) + 'static,
+    {
+        let callback: Box<TimerCallback> = Box::new(Box::new(callback));
+        let data = Box::into_raw(callback) as *mut c_void;
+        TimerId(raw::create_timer(
+            self.ctx,
+            period.num_milliseconds(),
+            Some(timer_callback),
+            data,
+        ))
+    }
+
+    /// Cancels a timer that hasn't fired yet, dropping its callback.
+    pub fn stop_timer(&self, id: TimerId) -> Result<(), RModError> {
+        let mut data: *mut c_void = ptr::null_mut();
+        match raw::stop_timer(self.ctx, id.0, &mut data) {
+            raw::Status::Ok => {
+                drop(unsafe { Box::from_raw(data as *mut TimerCallback) });
+                Ok(())
+            }
+
+            // Error occurs if the timer has already fired or never existed.
+            raw::Status::Err => Err(error!("Error while stopping timer, no such timer")),
+        }
+    }
+}
+
+extern "C" fn timer_callback(ctx: *mut raw::RedisModuleCtx, data: *mut c_void) {
+    let callback = unsafe { Box::from_raw(data as *mut TimerCallback) };
+    callback(&Redis::new(ctx));
+}
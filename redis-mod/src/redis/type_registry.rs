@@ -0,0 +1,62 @@
+use crate::error::RModError;
+use crate::redis::{raw, DataType, ModuleType, ModuleTypeBuilder};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+static GLOBAL: OnceLock<TypeRegistry> = OnceLock::new();
+
+/// `TypeRegistry` keeps every custom type a module creates, keyed by its
+/// name, so that commands can look them up instead of each type needing its
+/// own global.
+///
+/// Types are registered while the module loads (see
+/// `RedisModuleInitializer::register_type`) and live for as long as the
+/// module does, since Redis refuses to unload a module that created types.
+pub struct TypeRegistry {
+    types: RwLock<HashMap<String, &'static (dyn Any + Send + Sync)>>,
+}
+
+impl TypeRegistry {
+    /// Returns the registry shared by the whole module.
+    pub fn global() -> &'static TypeRegistry {
+        GLOBAL.get_or_init(|| TypeRegistry {
+            types: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Creates the type described by `builder` and registers it under its
+    /// name. Must be called from within `RedisModule_OnLoad`.
+    pub fn register<T: DataType + 'static>(
+        &self,
+        ctx: *mut raw::RedisModuleCtx,
+        builder: ModuleTypeBuilder<T>,
+    ) -> Result<(), RModError> {
+        let name = builder.name().to_string();
+        let mut types = self
+            .types
+            .write()
+            .map_err(|_| error!("Type registry lock is poisoned"))?;
+        if types.contains_key(&name) {
+            return Err(error!("Module type {} is already registered", name));
+        }
+        let module_type: &'static ModuleType<T> = Box::leak(Box::new(builder.create(ctx)?));
+        types.insert(name, module_type);
+        Ok(())
+    }
+
+    /// Looks up a registered type by name. Errors if there's no such type,
+    /// or if it was registered for a `DataType` other than `T`.
+    pub fn get<T: DataType + 'static>(&self, name: &str) -> Result<&'static ModuleType<T>, RModError> {
+        let types = self
+            .types
+            .read()
+            .map_err(|_| error!("Type registry lock is poisoned"))?;
+        match types.get(name) {
+            Some(module_type) => module_type
+                .downcast_ref::<ModuleType<T>>()
+                .ok_or_else(|| error!("Module type {} is registered for another DataType", name)),
+            None => Err(error!("Module type {} is not registered", name)),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::RModError;
+use crate::redis::{raw, Redis};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce(&Redis) + Send + 'static>;
+
+// A detached thread safe context is only ever used while holding the global
+// lock, which is what makes it safe to share between the worker threads.
+struct DetachedCtx(*mut raw::RedisModuleCtx);
+
+unsafe impl Send for DetachedCtx {}
+unsafe impl Sync for DetachedCtx {}
+
+// Holds the global lock for a worker until it goes out of scope, so that it's
+// released even if the job panics.
+struct GlobalLock<'a>(&'a DetachedCtx);
+
+impl<'a> GlobalLock<'a> {
+    fn acquire(ctx: &'a DetachedCtx) -> GlobalLock<'a> {
+        raw::thread_safe_context_lock(ctx.0);
+        GlobalLock(ctx)
+    }
+}
+
+impl<'a> Drop for GlobalLock<'a> {
+    fn drop(&mut self) {
+        raw::thread_safe_context_unlock(self.0 .0);
+    }
+}
+
+/// `WorkerPool` runs jobs on a fixed set of background threads, each of which
+/// may operate on Redis through a shared detached thread safe context.
+///
+/// Every job is run while holding the Redis global lock, so the `Redis` it's
+/// given can be used exactly like the one passed to `Command::run`. Keep jobs
+/// short and do any slow outbound work before the job is submitted (or in a
+/// separate job) because Redis can't serve clients while the lock is held.
+///
+/// Dropping the pool closes its queue without waiting for the workers, which
+/// may need the global lock held by the dropping thread to finish the jobs
+/// already queued. The workers exit once the queue is drained, and the last
+/// one out frees the context. Drop the pool from the main thread (or another
+/// one holding the global lock).
+///
+/// Since the workers can outlive the pool, a module that has created one
+/// must not be unloaded: the workers would go on running its code once it's
+/// unmapped. Refuse the unload from the module's unload function (see
+/// `rmod_unload!`).
+pub struct WorkerPool {
+    ctx:    Option<Arc<DetachedCtx>>,
+    sender: Option<SyncSender<Job>>,
+}
+
+impl WorkerPool {
+    /// Creates a pool of `size` threads with room for `capacity` queued jobs.
+    ///
+    /// `ctx` can be any context, such as the one given to
+    /// `RedisModule_OnLoad`. The pool doesn't keep a reference to it.
+    /// Requires Redis 6.0.9 or later.
+    pub fn new(
+        ctx: *mut raw::RedisModuleCtx,
+        size: usize,
+        capacity: usize,
+    ) -> Result<WorkerPool, RModError> {
+        let ctx = raw::get_detached_thread_safe_context(ctx)
+            .ok_or_else(|| error!("Worker pools require Redis 6.0.9 or later"))?;
+        let ctx = Arc::new(DetachedCtx(ctx));
+        let (sender, receiver) = sync_channel::<Job>(capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        // The workers are detached, see `Drop`.
+        for _ in 0..size {
+            let ctx = Arc::clone(&ctx);
+            let receiver = Arc::clone(&receiver);
+            thread::spawn(move || run_worker(ctx, &receiver));
+        }
+
+        Ok(WorkerPool {
+            ctx:    Some(ctx),
+            sender: Some(sender),
+        })
+    }
+
+    /// Queues a job to be run on one of the worker threads.
+    ///
+    /// This never blocks the calling thread (which is usually the Redis main
+    /// thread) and errors instead when the queue is already full.
+    pub fn submit<F>(&self, job: F) -> Result<(), RModError>
+    where
+        F: FnOnce(&Redis) + Send + 'static,
+    {
+        let sender = match self.sender {
+            Some(ref sender) => sender,
+            None => return Err(error!("Worker pool is shut down")),
+        };
+        match sender.try_send(Box::new(job)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(error!("Worker pool queue is full")),
+            Err(TrySendError::Disconnected(_)) => Err(error!("Worker pool is shut down")),
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    // Closes the queue and lets go of the detached context. Joining the
+    // workers here could deadlock, since a worker waiting for the global lock
+    // never finishes while the dropping thread holds it.
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(ctx) = self.ctx.take().and_then(Arc::into_inner) {
+            // No worker is left, and the global lock is already held here.
+            raw::free_thread_safe_context(ctx.0);
+        }
+    }
+}
+
+fn run_worker(ctx: Arc<DetachedCtx>, receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => break,
+        };
+        let job = match job {
+            Ok(job) => job,
+            Err(_) => break,
+        };
+
+        let _lock = GlobalLock::acquire(&ctx);
+        // A panicking job mustn't take the worker down with it.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| job(&Redis::new(ctx.0))));
+    }
+
+    // The last one out frees the context, which has to happen under the
+    // global lock. The lock isn't part of the context, so it's released
+    // through the same pointer once the context is freed.
+    if let Some(ctx) = Arc::into_inner(ctx) {
+        let ctx = ctx.0;
+        raw::thread_safe_context_lock(ctx);
+        raw::free_thread_safe_context(ctx);
+        raw::thread_safe_context_unlock(ctx);
+    }
+}
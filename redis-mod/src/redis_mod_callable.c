@@ -29,6 +29,24 @@ RedisModuleCallReply *RedisModule_Call3(RedisModuleCtx *ctx, const char *cmdname
     return RedisModule_Call(ctx, cmdname, "ccc", key, arg0, arg1);
 }
 
+RedisModuleCallReply *RedisModule_CallArgv(RedisModuleCtx *ctx, const char *cmdname, RedisModuleString **argv, size_t argc) {
+    return RedisModule_Call(ctx, cmdname, "v", argv, argc);
+}
+
+/* Same as RedisModule_CallArgv, but the command is also propagated to the
+ * AOF and replicas. */
+RedisModuleCallReply *RedisModule_CallArgvReplicate(RedisModuleCtx *ctx, const char *cmdname, RedisModuleString **argv, size_t argc) {
+    return RedisModule_Call(ctx, cmdname, "!v", argv, argc);
+}
+
+int RedisModule_ReplicateArgv(RedisModuleCtx *ctx, const char *cmdname, RedisModuleString **argv, size_t argc) {
+    return RedisModule_Replicate(ctx, cmdname, "v", argv, argc);
+}
+
+void RedisModule_EmitAOFArgv(RedisModuleIO *io, const char *cmdname, RedisModuleString **argv, size_t argc) {
+    RedisModule_EmitAOF(io, cmdname, "v", argv, argc);
+}
+
 RedisModuleCallReply *RedisModule_CallKeys(RedisModuleCtx *ctx, const char *arg0){
     return RedisModule_Call(ctx, "keys", "c", arg0);
 }
@@ -54,9 +72,20 @@ RedisModuleString *RedisModuleHash_Get(RedisModuleKey *key, RedisModuleString *f
     return oldval;
 }
 
+// RedisModule_HashGet takes its fields as variable arguments, which can't be
+// built up at runtime, so fetch them one by one from here instead.
+void RedisModuleHash_MGet(RedisModuleKey *key, RedisModuleString **fields, RedisModuleString **values, size_t len){
+    for (size_t i = 0; i < len; i++) {
+        values[i] = NULL;
+        RedisModule_HashGet(key, REDISMODULE_HASH_NONE, fields[i], &values[i], NULL);
+    }
+}
+
 int RedisModuleHash_Set(RedisModuleKey *key, RedisModuleString *field, RedisModuleString *val){
     RedisModule_HashSet(key, REDISMODULE_HASH_NONE, field, val, NULL);
     return REDISMODULE_OK;
 }
 
-
+int RedisModuleHash_Del(RedisModuleKey *key, int flags, RedisModuleString *field){
+    return RedisModule_HashSet(key, flags, field, REDISMODULE_HASH_DELETE, NULL);
+}
@@ -1,4 +1,8 @@
 #include <string.h>
+
+// Blocked clients, thread safe contexts, timers and the cluster API are still
+// flagged as experimental by Redis, so opt in to have them resolved at init.
+#define REDISMODULE_EXPERIMENTAL_API
 #include "redismodule.h"
 
 
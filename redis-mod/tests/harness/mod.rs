@@ -0,0 +1,244 @@
+// Helpers shared by the integration tests: a `redis-server` with the example
+// module loaded, and a minimal RESP client to run commands against it.
+//
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A reply read back from the server. RESP3 types are only sent to clients
+/// that switched protocols with `HELLO 3`.
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Status(String),
+    Error(String),
+    Integer(i64),
+    Bulk(String),
+    Array(Vec<Value>),
+    Nil,
+    Double(f64),
+    Bool(bool),
+    BigNumber(String),
+    Verbatim(String, String),
+    Map(Vec<(Value, Value)>),
+    Set(Vec<Value>),
+}
+
+/// A `redis-server` child process with the example module loaded. It's
+/// killed when dropped.
+pub struct Server {
+    child: Child,
+    port:  u16,
+}
+
+impl Server {
+    /// Builds the example module (once per test binary) and starts a server
+    /// on a free port with it loaded, waiting until it accepts connections.
+    pub fn start() -> Server {
+        Server::start_with_args(&[])
+    }
+
+    /// Same as `start`, also opening a connection to the server. The server
+    /// must be kept alive for as long as the client is used.
+    pub fn start_with_client() -> (Server, Client) {
+        let server = Server::start();
+        let client = server.client();
+        (server, client)
+    }
+
+    /// Same as `start`, but passes `args` to the module after its path.
+    pub fn start_with_args(args: &[&str]) -> Server {
+        let port = free_port();
+        let binary = env::var("REDIS_SERVER").unwrap_or_else(|_| "redis-server".to_owned());
+        let child = Command::new(&binary)
+            .arg("--port")
+            .arg(port.to_string())
+            .arg("--save")
+            .arg("")
+            .arg("--appendonly")
+            .arg("no")
+            .arg("--loadmodule")
+            .arg(module_path())
+            .args(args)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|e| panic!("could not start {}: {}", binary, e));
+
+        let server = Server { child, port };
+        server.wait_until_ready();
+        server
+    }
+
+    /// Returns the port the server listens on, e.g. to make another server
+    /// its replica.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Opens a new connection to the server.
+    pub fn client(&self) -> Client {
+        let stream = TcpStream::connect(("127.0.0.1", self.port)).expect("could not connect to redis-server");
+        Client {
+            reader: BufReader::new(stream.try_clone().expect("could not clone stream")),
+            stream,
+        }
+    }
+
+    fn wait_until_ready(&self) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Ok(stream) = TcpStream::connect(("127.0.0.1", self.port)) {
+                drop(stream);
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("redis-server didn't start listening on port {}", self.port);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Asserts that `reply` is an error whose message starts with `prefix`,
+/// usually its error code, and returns the message for further checks.
+pub fn assert_error(reply: Value, prefix: &str) -> String {
+    match reply {
+        Value::Error(message) => {
+            assert!(message.starts_with(prefix), "expected an error starting with {:?}, got {:?}", prefix, message);
+            message
+        }
+        reply => panic!("expected an error, got {:?}", reply),
+    }
+}
+
+/// A connection speaking just enough RESP to send commands and read replies.
+pub struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Client {
+    /// Sends a command and waits for its reply.
+    pub fn cmd(&mut self, args: &[&str]) -> Value {
+        let args: Vec<&[u8]> = args.iter().map(|arg| arg.as_bytes()).collect();
+        self.cmd_bytes(&args)
+    }
+
+    /// Same as `cmd`, for arguments that aren't valid UTF-8.
+    pub fn cmd_bytes(&mut self, args: &[&[u8]]) -> Value {
+        self.send_bytes(args);
+        self.read_value()
+    }
+
+    /// Sends a command without waiting for its reply, to pipeline commands
+    /// and then `read` their replies.
+    pub fn send(&mut self, args: &[&str]) {
+        let args: Vec<&[u8]> = args.iter().map(|arg| arg.as_bytes()).collect();
+        self.send_bytes(&args);
+    }
+
+    fn send_bytes(&mut self, args: &[&[u8]]) {
+        let mut request = format!("*{}\r\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg);
+            request.extend_from_slice(b"\r\n");
+        }
+        self.stream.write_all(&request).expect("could not send command");
+    }
+
+    /// Waits for the next message pushed by the server, e.g. once
+    /// subscribed to a channel.
+    pub fn read(&mut self) -> Value {
+        self.read_value()
+    }
+
+    fn read_line(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).expect("could not read reply");
+        line.trim_end_matches("\r\n").to_owned()
+    }
+
+    fn read_bulk(&mut self, len: usize) -> String {
+        let mut buf = vec![0; len + 2];
+        self.reader.read_exact(&mut buf).expect("could not read bulk reply");
+        buf.truncate(len);
+        String::from_utf8(buf).expect("bulk reply isn't UTF-8")
+    }
+
+    fn read_value(&mut self) -> Value {
+        let line = self.read_line();
+        let (kind, rest) = line.split_at(1);
+        match kind {
+            "+" => Value::Status(rest.to_owned()),
+            "-" => Value::Error(rest.to_owned()),
+            ":" => Value::Integer(rest.parse().expect("invalid integer reply")),
+            "$" => match rest.parse::<i64>().expect("invalid bulk length") {
+                -1 => Value::Nil,
+                len => Value::Bulk(self.read_bulk(len as usize)),
+            },
+            "*" => match rest.parse::<i64>().expect("invalid array length") {
+                -1 => Value::Nil,
+                len => Value::Array((0..len).map(|_| self.read_value()).collect()),
+            },
+            "_" => Value::Nil,
+            "," => Value::Double(rest.parse().expect("invalid double reply")),
+            "#" => Value::Bool(rest == "t"),
+            "(" => Value::BigNumber(rest.to_owned()),
+            "=" => {
+                let bulk = self.read_bulk(rest.parse().expect("invalid verbatim length"));
+                let (format, data) = bulk.split_at(3);
+                Value::Verbatim(format.to_owned(), data[1..].to_owned())
+            }
+            "%" => {
+                let len: usize = rest.parse().expect("invalid map length");
+                Value::Map((0..len).map(|_| (self.read_value(), self.read_value())).collect())
+            }
+            "~" => {
+                let len: usize = rest.parse().expect("invalid set length");
+                Value::Set((0..len).map(|_| self.read_value()).collect())
+            }
+            _ => panic!("unsupported reply: {}", line),
+        }
+    }
+}
+
+// Builds the example module and returns the path to the shared library.
+fn module_path() -> &'static PathBuf {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+        let status = Command::new(cargo)
+            .current_dir(&manifest_dir)
+            .args(["build", "--example", "hello"])
+            .status()
+            .expect("could not run cargo");
+        assert!(status.success(), "could not build the example module");
+
+        let target_dir = env::var("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| manifest_dir.join("target"));
+        target_dir
+            .join("debug")
+            .join("examples")
+            .join(format!("{}hello{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX))
+    })
+}
+
+fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not find a free port");
+    listener.local_addr().expect("could not find a free port").port()
+}
@@ -0,0 +1,1162 @@
+// End to end tests running the example module in a real `redis-server`.
+//
+// Run them with `cargo test --features dev`.
+#![cfg(feature = "dev")]
+
+mod harness;
+
+use harness::{assert_error, Client, Server, Value};
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn echo_replies_with_its_argument() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.echo", "hi"]), Value::Bulk("hi".to_owned()));
+}
+
+#[test]
+fn echo_rejects_wrong_number_of_arguments() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_error(client.cmd(&["hello.echo"]), "ERR wrong number of arguments");
+}
+
+#[test]
+fn incr_increments_the_key() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.incr", "counter"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.incr", "counter"]), Value::Integer(2));
+    assert_eq!(client.cmd(&["get", "counter"]), Value::Bulk("2".to_owned()));
+}
+
+#[test]
+fn incr_surfaces_errors_from_the_call() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "counter", "abc"]);
+    assert_error(client.cmd(&["hello.incr", "counter"]), "ERR value is not an integer");
+}
+
+#[test]
+fn get_reads_a_string_key() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.get", "greeting"]), Value::Nil);
+    client.cmd(&["set", "greeting", "hi"]);
+    assert_eq!(client.cmd(&["hello.get", "greeting"]), Value::Bulk("hi".to_owned()));
+}
+
+#[test]
+fn get_rejects_keys_of_the_wrong_type() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "list", "a"]);
+    assert_error(client.cmd(&["hello.get", "list"]), "WRONGTYPE");
+}
+
+#[test]
+fn client_reports_the_caller() {
+    let (_server, mut client) = Server::start_with_client();
+
+    match client.cmd(&["hello.client"]) {
+        Value::Array(values) => {
+            assert_ne!(values[0], Value::Integer(0));
+            assert_eq!(values[0], client.cmd(&["client", "id"]));
+            assert_eq!(values[1], Value::Bulk("127.0.0.1".to_owned()));
+        }
+        reply => panic!("expected an array, got {:?}", reply),
+    }
+}
+
+#[test]
+fn words_sets_the_array_length_afterwards() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["hello.words", "a bb  ccc"]),
+        Value::Array(vec![
+            Value::Bulk("a".to_owned()),
+            Value::Bulk("bb".to_owned()),
+            Value::Bulk("ccc".to_owned()),
+        ])
+    );
+    assert_eq!(client.cmd(&["hello.words", ""]), Value::Array(vec![]));
+}
+
+#[test]
+fn readme_is_a_verbatim_string() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let text = "# hello\nAn example module.".to_owned();
+    assert_eq!(client.cmd(&["hello.readme"]), Value::Bulk(text.clone()));
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(client.cmd(&["hello.readme"]), Value::Verbatim("mkd".to_owned(), text));
+}
+
+#[test]
+fn big_replies_with_a_big_number() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let digits = "1234567890123456789012345678901234567890";
+    assert_eq!(client.cmd(&["hello.big", digits]), Value::Bulk(digits.to_owned()));
+    let message = assert_error(client.cmd(&["hello.big", "12a"]), "ERR");
+    assert!(message.contains("base 10 integer"), "{}", message);
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(client.cmd(&["hello.big", digits]), Value::BigNumber(digits.to_owned()));
+}
+
+#[test]
+fn keys_scans_the_whole_keyspace() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let pairs: Vec<String> = (0..1000).flat_map(|i| vec![format!("key:{}", i), i.to_string()]).collect();
+    let mut mset = vec!["mset"];
+    mset.extend(pairs.iter().map(String::as_str));
+    client.cmd(&mset);
+
+    // Like SCAN, a key may come back twice while the keyspace is rehashed.
+    let count = |reply: Value| match reply {
+        Value::Array(keys) => keys.iter().map(|key| format!("{:?}", key)).collect::<HashSet<_>>().len(),
+        reply => panic!("expected an array, got {:?}", reply),
+    };
+    assert_eq!(count(client.cmd(&["hello.keys"])), 1000);
+    assert_eq!(count(client.cmd(&["hello.keys", "key:1*"])), 111);
+}
+
+#[test]
+fn hfields_scans_every_field_of_a_hash() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let pairs: Vec<String> = (0..500).flat_map(|i| vec![format!("field:{}", i), i.to_string()]).collect();
+    let mut hset = vec!["hset", "hash"];
+    hset.extend(pairs.iter().map(String::as_str));
+    client.cmd(&hset);
+
+    assert_eq!(client.cmd(&["hello.hfields", "hash"]), Value::Integer(500));
+    assert_eq!(client.cmd(&["hello.hfields", "missing"]), Value::Integer(0));
+}
+
+#[test]
+fn getrange_matches_native_getrange() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "mykey", "This is a string"]);
+    for (start, end) in &[("0", "3"), ("-3", "-1"), ("0", "-1"), ("10", "100"), ("5", "3"), ("-100", "2"), ("-1", "-3")] {
+        assert_eq!(
+            client.cmd(&["hello.getrange", "mykey", start, end]),
+            client.cmd(&["getrange", "mykey", start, end])
+        );
+    }
+    assert_eq!(client.cmd(&["hello.getrange", "missing", "0", "-1"]), Value::Bulk(String::new()));
+}
+
+#[test]
+fn setrange_matches_native_setrange() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "mykey", "Hello World"]);
+    assert_eq!(client.cmd(&["hello.setrange", "mykey", "6", "Redis"]), Value::Integer(11));
+    assert_eq!(client.cmd(&["get", "mykey"]), Value::Bulk("Hello Redis".to_owned()));
+    assert_eq!(client.cmd(&["hello.setrange", "other", "2", "ab"]), Value::Integer(4));
+    assert_eq!(client.cmd(&["get", "other"]), Value::Bulk("\0\0ab".to_owned()));
+}
+
+#[test]
+fn setrange_rejects_oversized_strings() {
+    let (_server, mut client) = Server::start_with_client();
+
+    // Past the default `proto-max-bulk-len` of 512MB.
+    for offset in ["536870911", &usize::MAX.to_string()] {
+        let message = assert_error(client.cmd(&["hello.setrange", "huge", offset, "ab"]), "ERR");
+        assert!(message.contains("proto-max-bulk-len"), "{}", message);
+    }
+    assert_eq!(client.cmd(&["exists", "huge"]), Value::Integer(0));
+}
+
+#[test]
+fn lrange_matches_native_lrange() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "list", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+    assert_eq!(
+        client.cmd(&["hello.lrange", "list", "-3", "-1"]),
+        Value::Array(vec![Value::Bulk("7".to_owned()), Value::Bulk("8".to_owned()), Value::Bulk("9".to_owned())])
+    );
+    for (start, stop) in &[("0", "-1"), ("2", "4"), ("-100", "1"), ("8", "100"), ("5", "2")] {
+        assert_eq!(
+            client.cmd(&["hello.lrange", "list", start, stop]),
+            client.cmd(&["lrange", "list", start, stop])
+        );
+    }
+
+    client.cmd(&["set", "string", "abc"]);
+    let message = assert_error(client.cmd(&["hello.lrange", "string", "0", "-1"]), "ERR");
+    assert!(message.contains("not List structure"), "{}", message);
+}
+
+#[test]
+fn lindex_gets_elements_by_position() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "list", "a", "b", "c"]);
+    assert_eq!(client.cmd(&["hello.lindex", "list", "0"]), Value::Bulk("a".to_owned()));
+    assert_eq!(client.cmd(&["hello.lindex", "list", "2"]), Value::Bulk("c".to_owned()));
+    assert_eq!(client.cmd(&["hello.lindex", "list", "-1"]), Value::Bulk("c".to_owned()));
+    assert_eq!(client.cmd(&["hello.lindex", "list", "3"]), Value::Nil);
+    assert_eq!(client.cmd(&["hello.lindex", "list", "-4"]), Value::Nil);
+    assert_eq!(client.cmd(&["hello.lindex", "missing", "0"]), Value::Nil);
+}
+
+#[test]
+fn lset_and_linsert_modify_a_list_in_place() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "list", "a", "b", "c"]);
+    assert_eq!(client.cmd(&["hello.lset", "list", "1", "B"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["hello.linsert", "list", "0", "head"]), Value::Integer(4));
+    assert_eq!(client.cmd(&["hello.linsert", "list", "-1", "tail"]), Value::Integer(5));
+    assert_eq!(
+        client.cmd(&["lrange", "list", "0", "-1"]),
+        Value::Array(
+            ["head", "a", "B", "c", "tail"].iter().map(|&ele| Value::Bulk(ele.to_owned())).collect()
+        )
+    );
+
+    let message = assert_error(client.cmd(&["hello.lset", "list", "10", "x"]), "ERR");
+
+    assert!(message.contains("index out of range"), "{}", message);
+}
+
+#[test]
+fn reload_dumps_and_restores_a_list() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "list", "a", "b", "c"]);
+    let before = client.cmd(&["lrange", "list", "0", "-1"]);
+    assert_eq!(client.cmd(&["hello.reload", "list"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["lrange", "list", "0", "-1"]), before);
+
+    let message = assert_error(client.cmd(&["hello.reload", "missing"]), "ERR");
+
+    assert!(message.contains("no such key"), "{}", message);
+}
+
+#[test]
+fn strlen_reads_binary_values() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd_bytes(&[b"set", b"binary", &[0xff, 0xfe, 0x00, 0x80]]);
+    assert_eq!(client.cmd(&["hello.strlen", "binary"]), Value::Integer(4));
+}
+
+#[test]
+fn hex_reads_binary_keys() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd_bytes(&[b"set", b"gif", &[0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0xff]]);
+    assert_eq!(client.cmd(&["hello.hex", "gif"]), Value::Bulk("4749463839610100ff".to_owned()));
+    assert_eq!(client.cmd(&["hello.hex", "missing"]), Value::Nil);
+}
+
+#[test]
+fn inspect_shows_a_redis_string() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["hello.inspect", "héllo"]),
+        Value::Array(vec![
+            Value::Bulk("héllo".to_owned()),
+            Value::Bulk("<héllo>".to_owned()),
+            Value::Integer(6),
+        ])
+    );
+}
+
+#[test]
+fn filter_rewrites_aliased_gets() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "hello:greeting", "hi"]);
+    assert_eq!(client.cmd(&["get", "@greeting"]), Value::Bulk("hi".to_owned()));
+    assert_eq!(client.cmd(&["get", "greeting"]), Value::Nil);
+}
+
+#[test]
+fn cluster_reports_no_cluster() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.cluster"]), Value::Array(vec![Value::Nil, Value::Integer(0)]));
+}
+
+#[test]
+fn broadcast_needs_a_cluster() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let message = assert_error(client.cmd(&["hello.broadcast", "hi"]), "ERR");
+
+    assert!(message.contains("not in cluster mode"), "{}", message);
+}
+
+#[test]
+fn hset_fires_a_keyspace_event() {
+    let (server, mut client) = Server::start_with_client();
+    let mut subscriber = server.client();
+
+    client.cmd(&["config", "set", "notify-keyspace-events", "Eh"]);
+    subscriber.cmd(&["psubscribe", "__keyevent@0__:*"]);
+    assert_eq!(client.cmd(&["hello.hset", "hash", "field", "value"]), Value::Status("OK".to_owned()));
+
+    // Newer servers also fire `hset` for writes through the hash API.
+    let expected = Value::Array(vec![
+        Value::Bulk("pmessage".to_owned()),
+        Value::Bulk("__keyevent@0__:*".to_owned()),
+        Value::Bulk("__keyevent@0__:hello.hset".to_owned()),
+        Value::Bulk("hash".to_owned()),
+    ]);
+    let mut message = subscriber.read();
+    if message != expected {
+        message = subscriber.read();
+    }
+    assert_eq!(message, expected);
+}
+
+#[test]
+fn incrby_increments_through_the_module_api() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.incrby", "fresh", "5"]), Value::Integer(5));
+    client.cmd(&["set", "existing", "10", "ex", "100"]);
+    assert_eq!(client.cmd(&["hello.incrby", "existing", "-3"]), Value::Integer(7));
+    assert_eq!(client.cmd(&["get", "existing"]), Value::Bulk("7".to_owned()));
+    match client.cmd(&["ttl", "existing"]) {
+        Value::Integer(ttl) => assert!(ttl > 90, "expiry was lost, ttl is {}", ttl),
+        reply => panic!("expected an integer, got {:?}", reply),
+    }
+
+    client.cmd(&["set", "text", "abc"]);
+    assert_eq!(
+        client.cmd(&["hello.incrby", "text", "1"]),
+        Value::Error("ERR value is not an integer or out of range".to_owned())
+    );
+}
+
+#[test]
+fn getset_swaps_the_value() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.getset", "key", "first"]), Value::Nil);
+    assert_eq!(client.cmd(&["hello.getset", "key", "second"]), Value::Bulk("first".to_owned()));
+    assert_eq!(client.cmd(&["get", "key"]), Value::Bulk("second".to_owned()));
+}
+
+#[test]
+fn time_increases() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let times = |reply: Value| match reply {
+        Value::Array(values) => match values.as_slice() {
+            [Value::Integer(now), Value::Integer(monotonic)] => (*now, *monotonic),
+            values => panic!("expected two integers, got {:?}", values),
+        },
+        reply => panic!("expected an array, got {:?}", reply),
+    };
+    let (now1, monotonic1) = times(client.cmd(&["hello.time"]));
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let (now2, monotonic2) = times(client.cmd(&["hello.time"]));
+
+    assert!(now1 > 0 && monotonic1 > 0);
+    assert!(now2 >= now1);
+    assert!(monotonic2 > monotonic1);
+}
+
+#[test]
+fn random_returns_existing_key() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.random"]), Value::Nil);
+
+    let keys = ["random:a", "random:b", "random:c"];
+    for key in keys {
+        client.cmd(&["set", key, "1"]);
+    }
+    for _ in 0..10 {
+        match client.cmd(&["hello.random"]) {
+            Value::Bulk(key) => assert!(keys.contains(&key.as_str()), "unexpected key {}", key),
+            reply => panic!("expected a key, got {:?}", reply),
+        }
+    }
+}
+
+#[test]
+fn dbsize_counts_keys() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.dbsize"]), Value::Integer(0));
+    for i in 0..5 {
+        client.cmd(&["set", &format!("dbsize:{}", i), "1"]);
+    }
+    assert_eq!(client.cmd(&["hello.dbsize"]), Value::Integer(5));
+}
+
+#[test]
+fn xadd_generates_increasing_ids() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let parse_id = |reply: Value| match reply {
+        Value::Bulk(id) => {
+            let (ms, seq) = id.split_once('-').expect("entry ID");
+            (ms.parse::<u64>().unwrap(), seq.parse::<u64>().unwrap())
+        }
+        reply => panic!("expected an entry ID, got {:?}", reply),
+    };
+    let first = parse_id(client.cmd(&["hello.xadd", "events", "name", "a"]));
+    let second = parse_id(client.cmd(&["hello.xadd", "events", "name", "b", "size", "2"]));
+
+    assert!(second > first);
+    assert_eq!(client.cmd(&["xlen", "events"]), Value::Integer(2));
+    assert_eq!(client.cmd(&["type", "events"]), Value::Status("stream".to_owned()));
+
+    client.cmd(&["set", "events:str", "x"]);
+    assert_error(client.cmd(&["hello.xadd", "events:str", "name", "a"]), "ERR");
+}
+
+#[test]
+fn xrange_returns_entries() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["xadd", "events", "1-1", "name", "a"]);
+    client.cmd(&["xadd", "events", "2-1", "name", "b", "size", "2"]);
+    client.cmd(&["xadd", "events", "3-1", "name", "c"]);
+
+    let entry = |id: &str, fields: &[&str]| {
+        Value::Array(vec![
+            Value::Bulk(id.to_owned()),
+            Value::Array(fields.iter().map(|&f| Value::Bulk(f.to_owned())).collect()),
+        ])
+    };
+    assert_eq!(
+        client.cmd(&["hello.xrange", "events", "-", "+"]),
+        Value::Array(vec![
+            entry("1-1", &["name", "a"]),
+            entry("2-1", &["name", "b", "size", "2"]),
+            entry("3-1", &["name", "c"]),
+        ])
+    );
+    assert_eq!(
+        client.cmd(&["hello.xrange", "events", "2", "3-0"]),
+        Value::Array(vec![entry("2-1", &["name", "b", "size", "2"])])
+    );
+    assert_eq!(client.cmd(&["hello.xrange", "events", "3", "1"]), Value::Array(vec![]));
+    assert_eq!(client.cmd(&["hello.xrange", "missing", "-", "+"]), Value::Array(vec![]));
+}
+
+#[test]
+fn peek_does_not_touch_key() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "peeked", "v"]);
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+
+    assert_eq!(client.cmd(&["hello.peek", "peeked"]), Value::Bulk("v".to_owned()));
+    match client.cmd(&["object", "idletime", "peeked"]) {
+        Value::Integer(idle) => assert!(idle >= 2, "idle time reset to {}", idle),
+        reply => panic!("expected an integer, got {:?}", reply),
+    }
+
+    assert_eq!(client.cmd(&["hello.get", "peeked"]), Value::Bulk("v".to_owned()));
+    assert_eq!(client.cmd(&["object", "idletime", "peeked"]), Value::Integer(0));
+}
+
+#[test]
+fn idle_reports_lru_and_lfu() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "fresh", "v"]);
+    match client.cmd(&["hello.idle", "fresh"]) {
+        Value::Integer(idle) => assert!(idle <= 1, "idle for {}s", idle),
+        reply => panic!("expected an idle time, got {:?}", reply),
+    }
+    assert_error(client.cmd(&["hello.idle", "missing"]), "ERR");
+
+    client.cmd(&["config", "set", "maxmemory-policy", "allkeys-lfu"]);
+    client.cmd(&["set", "frequent", "v"]);
+    match client.cmd(&["hello.idle", "frequent"]) {
+        Value::Integer(freq) => assert!(freq > 0, "frequency of {}", freq),
+        reply => panic!("expected an access frequency, got {:?}", reply),
+    }
+}
+
+#[test]
+fn failing_command_replies_once() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["rpush", "failing:list", "a"]);
+    client.send(&["hello.incrby", "failing:counter", "abc"]);
+    client.send(&["hello.get", "failing:list"]);
+    client.send(&["ping"]);
+
+    assert_error(client.read(), "ERR invalid digit");
+    assert_error(client.read(), "WRONGTYPE");
+    assert_eq!(client.read(), Value::Status("PONG".to_owned()));
+    assert_eq!(client.cmd(&["ping"]), Value::Status("PONG".to_owned()));
+}
+
+#[test]
+fn failing_after_reply_replies_once() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.send(&["hello.halfway", "done"]);
+    client.send(&["ping"]);
+
+    assert_eq!(client.read(), Value::Bulk("done".to_owned()));
+    assert_eq!(client.read(), Value::Status("PONG".to_owned()));
+    assert_eq!(client.cmd(&["ping"]), Value::Status("PONG".to_owned()));
+}
+
+#[test]
+fn unrepliable_nested_value_replies_error_only() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.send(&["hello.partial"]);
+    client.send(&["ping"]);
+
+    let message = assert_error(client.read(), "ERR");
+
+    assert!(message.contains("Unknown"), "{}", message);
+    assert_eq!(client.read(), Value::Status("PONG".to_owned()));
+}
+
+#[test]
+fn rename_moves_the_value_or_passes_on_the_error() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "from", "v"]);
+    assert_eq!(client.cmd(&["hello.rename", "from", "to"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "to"]), Value::Bulk("v".to_owned()));
+    assert_eq!(client.cmd(&["exists", "from"]), Value::Integer(0));
+
+    let message = assert_error(client.cmd(&["hello.rename", "missing", "to"]), "ERR");
+
+    assert!(message.contains("no such key"), "{}", message);
+}
+
+#[test]
+fn select_db_writes_to_the_other_database() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.setin", "3", "k", "v"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "k"]), Value::Nil);
+    client.cmd(&["select", "3"]);
+    assert_eq!(client.cmd(&["get", "k"]), Value::Bulk("v".to_owned()));
+
+    let message = assert_error(client.cmd(&["hello.setin", "100000", "k", "v"]), "ERR");
+
+    assert!(message.contains("out of range"), "{}", message);
+    assert_eq!(client.cmd(&["get", "k"]), Value::Bulk("v".to_owned()));
+}
+
+#[test]
+fn info_shows_the_module_section() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let info = match client.cmd(&["info", "hello"]) {
+        Value::Bulk(info) => info,
+        reply => panic!("expected a bulk string, got {:?}", reply),
+    };
+    assert!(info.contains("# hello_stats"), "{}", info);
+    assert!(info.contains("hello_loaded_args:"), "{}", info);
+}
+
+#[test]
+fn truncate_cuts_or_pads_strings() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "s", "hello"]);
+    assert_eq!(client.cmd(&["hello.truncate", "s", "2"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "s"]), Value::Bulk("he".to_owned()));
+    client.cmd(&["hello.truncate", "s", "4"]);
+    assert_eq!(client.cmd(&["get", "s"]), Value::Bulk("he\0\0".to_owned()));
+
+    client.cmd(&["rpush", "l", "a"]);
+    let message = assert_error(client.cmd(&["hello.truncate", "l", "1"]), "ERR");
+    assert!(message.contains("not String"), "{}", message);
+}
+
+#[test]
+fn replicate_propagates_the_given_command() {
+    let master = Server::start();
+    let replica = Server::start();
+    let mut client = master.client();
+    let mut replica_client = replica.client();
+
+    let port = master.port().to_string();
+    replica_client.cmd(&["replicaof", "127.0.0.1", port.as_str()]);
+    assert_eq!(client.cmd(&["hello.mark", "k", "v"]), Value::Status("OK".to_owned()));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while replica_client.cmd(&["get", "k"]) != Value::Bulk("v".to_owned()) {
+        assert!(Instant::now() < deadline, "the replica never got the key");
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn context_flags_tell_multi_and_scripts_apart() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let master = || Value::Bulk("master".to_owned());
+    assert_eq!(client.cmd(&["hello.flags"]), Value::Array(vec![master()]));
+
+    client.cmd(&["multi"]);
+    client.cmd(&["hello.flags"]);
+    assert_eq!(
+        client.cmd(&["exec"]),
+        Value::Array(vec![Value::Array(vec![Value::Bulk("multi".to_owned()), master()])])
+    );
+
+    assert_eq!(
+        client.cmd(&["eval", "return redis.call('hello.flags')", "0"]),
+        Value::Array(vec![Value::Bulk("lua".to_owned()), master()])
+    );
+}
+
+#[test]
+fn config_set_updates_the_module_config() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["config", "get", "hello.limit"]),
+        Value::Array(vec![Value::Bulk("hello.limit".to_owned()), Value::Bulk("10".to_owned())])
+    );
+    assert_eq!(client.cmd(&["config", "set", "hello.limit", "20"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["hello.limit"]), Value::Integer(20));
+
+    assert_error(client.cmd(&["config", "set", "hello.limit", "0"]), "ERR");
+    assert_eq!(client.cmd(&["hello.limit"]), Value::Integer(20));
+}
+
+#[test]
+fn type_tells_kinds_of_values_apart() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "s", "v"]);
+    client.cmd(&["rpush", "l", "a"]);
+    client.cmd(&["hset", "h", "f", "v"]);
+    assert_eq!(client.cmd(&["hello.type", "s"]), Value::Bulk("string".to_owned()));
+    assert_eq!(client.cmd(&["hello.type", "l"]), Value::Bulk("list".to_owned()));
+    assert_eq!(client.cmd(&["hello.type", "h"]), Value::Bulk("hash".to_owned()));
+    assert_eq!(client.cmd(&["hello.type", "missing"]), Value::Bulk("empty".to_owned()));
+}
+
+#[test]
+fn hgetall_reads_every_field() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["hset", "h", "a", "1", "b", "2"]);
+    let mut pairs = Vec::new();
+    match client.cmd(&["hello.hgetall", "h"]) {
+        Value::Array(elements) => {
+            let mut elements = elements.into_iter();
+            while let (Some(Value::Bulk(field)), Some(Value::Bulk(value))) = (elements.next(), elements.next()) {
+                pairs.push((field, value));
+            }
+        }
+        reply => panic!("expected an array, got {:?}", reply),
+    }
+    pairs.sort();
+    assert_eq!(pairs, vec![("a".to_owned(), "1".to_owned()), ("b".to_owned(), "2".to_owned())]);
+    assert_eq!(client.cmd(&["hello.hgetall", "missing"]), Value::Array(vec![]));
+
+    client.cmd(&["set", "s", "v"]);
+    let message = assert_error(client.cmd(&["hello.hgetall", "s"]), "ERR");
+    assert!(message.contains("not Hash"), "{}", message);
+}
+
+#[test]
+fn hdel_removes_a_field() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["hset", "h", "a", "1", "b", "2"]);
+    assert_eq!(client.cmd(&["hello.hdel", "h", "a"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.hdel", "h", "a"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.hdel", "missing", "a"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hkeys", "h"]), Value::Array(vec![Value::Bulk("b".to_owned())]));
+}
+
+#[test]
+fn later_sets_the_key_from_a_timer() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.later", "k", "v", "100"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "k"]), Value::Nil);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while client.cmd(&["get", "k"]) != Value::Bulk("v".to_owned()) {
+        assert!(Instant::now() < deadline, "the timer never fired");
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn sleep_blocks_only_its_client() {
+    let (server, mut client) = Server::start_with_client();
+    let mut other = server.client();
+
+    client.send(&["hello.sleep", "300"]);
+    assert_eq!(other.cmd(&["ping"]), Value::Status("PONG".to_owned()));
+    assert_eq!(client.read(), Value::Bulk("slept".to_owned()));
+
+    assert_eq!(client.cmd(&["hello.sleep", "3000"]), Value::Nil);
+}
+
+#[test]
+fn bgset_sets_the_key_from_a_thread() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.bgset", "k", "v"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "k"]), Value::Bulk("v".to_owned()));
+}
+
+#[test]
+fn tally_is_a_custom_type() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.tally", "t"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.tally", "t"]), Value::Integer(2));
+    assert_eq!(client.cmd(&["type", "t"]), Value::Status("hellotaly".to_owned()));
+
+    // Goes through the type's RDB callbacks.
+    client.cmd(&["hello.reload", "t"]);
+    assert_eq!(client.cmd(&["hello.tally", "t"]), Value::Integer(3));
+
+    client.cmd(&["set", "s", "v"]);
+    let message = assert_error(client.cmd(&["hello.tally", "s"]), "ERR");
+    assert!(message.contains("not Module"), "{}", message);
+}
+
+#[test]
+fn errors_are_replied_with_their_code() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["hello.fail", "reply", "WRONGTYPE", "not a tally"]),
+        Value::Error("WRONGTYPE not a tally".to_owned())
+    );
+    assert_eq!(
+        client.cmd(&["hello.fail", "return", "NOPERM", "not allowed"]),
+        Value::Error("NOPERM not allowed".to_owned())
+    );
+
+    // Uncoded errors get the generic code.
+    let message = assert_error(client.cmd(&["hello.fail", "other", "ERR", "x"]), "ERR ");
+    assert!(message.contains("Unknown mode: other"), "{}", message);
+    for code in &["", "not ok", "wrongtype"] {
+        let message = assert_error(client.cmd(&["hello.fail", "reply", code, "x"]), "ERR ");
+        assert!(message.contains("Invalid error code"), "{}", message);
+    }
+}
+
+#[test]
+fn same_key_as_compares_key_names() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.same", "a", "a"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.same", "a", "b"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.same", "a", "A"]), Value::Integer(0));
+}
+
+#[test]
+fn exists_tells_empty_strings_from_missing_keys() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "empty", ""]);
+    client.cmd(&["rpush", "l", "a"]);
+    assert_eq!(client.cmd(&["hello.exists", "empty"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.exists", "l"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.exists", "missing"]), Value::Integer(0));
+}
+
+#[test]
+fn pttl_reads_the_expiry() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "k", "v", "px", "100000"]);
+    match client.cmd(&["hello.pttl", "k"]) {
+        Value::Integer(ttl) => assert!(ttl > 90_000 && ttl <= 100_000, "{}", ttl),
+        reply => panic!("expected an integer, got {:?}", reply),
+    }
+
+    client.cmd(&["set", "forever", "v"]);
+    assert_eq!(client.cmd(&["hello.pttl", "forever"]), Value::Integer(-1));
+}
+
+#[test]
+fn persist_clears_the_expiry() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "k", "v", "px", "100000"]);
+    assert_eq!(client.cmd(&["hello.persist", "k"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["pttl", "k"]), Value::Integer(-1));
+    assert_eq!(client.cmd(&["get", "k"]), Value::Bulk("v".to_owned()));
+
+    assert_eq!(client.cmd(&["hello.persist", "k"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.persist", "missing"]), Value::Integer(0));
+}
+
+#[test]
+fn zadd_and_zscore_go_through_the_module_api() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.zadd", "z", "1.5", "a"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["hello.zadd", "z", "2", "b"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["hello.zadd", "z", "3", "a"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["zcard", "z"]), Value::Integer(2));
+    assert_eq!(client.cmd(&["hello.zscore", "z", "a"]), Value::Bulk("3".to_owned()));
+    assert_eq!(client.cmd(&["hello.zscore", "z", "b"]), Value::Bulk("2".to_owned()));
+    assert_eq!(client.cmd(&["hello.zscore", "z", "missing"]), Value::Nil);
+    assert_eq!(client.cmd(&["hello.zscore", "missing", "a"]), Value::Nil);
+
+    client.cmd(&["set", "s", "v"]);
+    assert_error(client.cmd(&["hello.zadd", "s", "1", "a"]), "ERR");
+    assert_error(client.cmd(&["hello.zscore", "s", "a"]), "ERR");
+}
+
+#[test]
+fn zrange_matches_native_zrange() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["zadd", "z", "1", "a", "2", "b", "3", "c", "4.5", "d"]);
+    for (start, stop) in &[("0", "-1"), ("1", "2"), ("-2", "-1"), ("-100", "1"), ("3", "100"), ("3", "1")] {
+        assert_eq!(
+            client.cmd(&["hello.zrange", "z", start, stop]),
+            client.cmd(&["zrange", "z", start, stop, "withscores"])
+        );
+    }
+    assert_eq!(client.cmd(&["hello.zrange", "missing", "0", "-1"]), Value::Array(Vec::new()));
+
+    client.cmd(&["set", "string", "abc"]);
+    let message = assert_error(client.cmd(&["hello.zrange", "string", "0", "-1"]), "ERR");
+    assert!(message.contains("not Zset structure"), "{}", message);
+}
+
+#[test]
+fn sumkeys_finds_its_keys_through_the_key_spec() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "a", "1"]);
+    client.cmd(&["set", "b", "2"]);
+    assert_eq!(client.cmd(&["hello.sumkeys", "3", "a", "b", "missing"]), Value::Integer(3));
+    assert_eq!(
+        client.cmd(&["command", "getkeys", "hello.sumkeys", "2", "a", "b"]),
+        Value::Array(vec![Value::Bulk("a".to_owned()), Value::Bulk("b".to_owned())])
+    );
+}
+
+#[test]
+fn sadd_and_sismember_call_the_set_commands() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.sadd", "s", "a"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.sadd", "s", "a"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["scard", "s"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.sismember", "s", "a"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.sismember", "s", "b"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.sismember", "missing", "a"]), Value::Integer(0));
+
+    client.cmd(&["set", "string", "v"]);
+    assert_error(client.cmd(&["hello.sadd", "string", "a"]), "WRONGTYPE");
+}
+
+#[test]
+fn del_deletes_or_unlinks_the_key() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "a", "1"]);
+    client.cmd(&["rpush", "b", "1", "2", "3"]);
+    assert_eq!(client.cmd(&["hello.del", "a"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["hello.del", "b", "unlink"]), Value::Integer(1));
+    assert_eq!(client.cmd(&["exists", "a", "b"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.del", "a"]), Value::Integer(0));
+    assert_eq!(client.cmd(&["hello.del", "a", "unlink"]), Value::Integer(0));
+}
+
+#[test]
+fn key_spec_tells_redis_where_the_keys_are() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["command", "getkeys", "hello.get", "k"]),
+        Value::Array(vec![Value::Bulk("k".to_owned())])
+    );
+    assert_eq!(
+        client.cmd(&["command", "getkeys", "hello.same", "a", "b"]),
+        Value::Array(vec![Value::Bulk("a".to_owned()), Value::Bulk("b".to_owned())])
+    );
+    let message = assert_error(client.cmd(&["command", "getkeys", "hello.echo", "message"]), "ERR");
+    assert!(message.contains("no key arguments"), "{}", message);
+}
+
+#[test]
+fn mset_ignores_slots_outside_of_cluster_mode() {
+    let (_server, mut client) = Server::start_with_client();
+
+    // "foo" and "bar" hash to different slots.
+    assert_eq!(client.cmd(&["hello.mset", "foo", "1", "bar", "2"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["get", "foo"]), Value::Bulk("1".to_owned()));
+    assert_eq!(client.cmd(&["get", "bar"]), Value::Bulk("2".to_owned()));
+}
+
+#[test]
+fn card_counts_elements_of_every_type() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "string", "abc"]);
+    client.cmd(&["rpush", "list", "a", "b"]);
+    client.cmd(&["sadd", "set", "a", "b", "c", "d"]);
+    client.cmd(&["hset", "hash", "f", "v"]);
+    client.cmd(&["zadd", "zset", "1", "a", "2", "b", "3", "c"]);
+    client.cmd(&["xadd", "stream", "*", "f", "v"]);
+    for (key, len) in &[("string", 3), ("list", 2), ("set", 4), ("hash", 1), ("zset", 3), ("stream", 1), ("missing", 0)] {
+        assert_eq!(client.cmd(&["hello.card", key]), Value::Integer(*len), "{}", key);
+    }
+}
+
+#[test]
+fn call_rejects_nul_bytes_in_the_command_name() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.call", "set", "k", "a\0b"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["strlen", "k"]), Value::Integer(3));
+
+    let message = assert_error(client.cmd_bytes(&[b"hello.call", b"get\0x", b"k"]), "ERR");
+
+    assert!(message.contains("nul byte"), "{}", message);
+}
+
+#[test]
+fn strlens_calls_commands_between_elements() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["set", "a", "1"]);
+    client.cmd(&["set", "c", "333"]);
+    assert_eq!(
+        client.cmd(&["hello.strlens", "a", "missing", "c"]),
+        Value::Array(vec![
+            Value::Bulk("a".to_owned()),
+            Value::Integer(1),
+            Value::Bulk("c".to_owned()),
+            Value::Integer(3),
+        ])
+    );
+    assert_eq!(client.cmd(&["hello.strlens", "missing"]), Value::Array(Vec::new()));
+
+    // An error from a call in the middle still leaves a valid reply behind.
+    client.cmd(&["rpush", "list", "x"]);
+    match client.cmd(&["hello.strlens", "a", "list"]) {
+        Value::Array(elements) => assert_eq!(elements.len(), 2),
+        reply => panic!("expected an array, got {:?}", reply),
+    }
+    assert_eq!(client.cmd(&["ping"]), Value::Status("PONG".to_owned()));
+}
+
+#[test]
+fn unique_is_a_set_in_resp3_only() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let elements = || vec![Value::Bulk("a".to_owned()), Value::Bulk("b".to_owned())];
+    assert_eq!(client.cmd(&["hello.unique", "a", "b", "a"]), Value::Array(elements()));
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(client.cmd(&["hello.unique", "a", "b", "a"]), Value::Set(elements()));
+}
+
+#[test]
+fn config_set_applies_only_accepted_values() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let applied = |client: &mut Client| match client.cmd(&["hello.applied"]) {
+        Value::Integer(n) => n,
+        reply => panic!("expected an integer, got {:?}", reply),
+    };
+    let before = applied(&mut client);
+    assert_eq!(client.cmd(&["config", "set", "hello.limit", "20"]), Value::Status("OK".to_owned()));
+    assert_eq!(applied(&mut client), before + 1);
+
+    // Out of bounds, so rejected before being set or applied.
+    assert_error(client.cmd(&["config", "set", "hello.limit", "1001"]), "ERR");
+    assert_eq!(applied(&mut client), before + 1);
+    assert_eq!(client.cmd(&["hello.limit"]), Value::Integer(20));
+}
+
+#[test]
+fn proxy_passes_call_replies_on_as_is() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["xadd", "stream", "1-1", "a", "1", "b", "2"]);
+    client.cmd(&["xadd", "stream", "2-1", "c", "3"]);
+    assert_eq!(
+        client.cmd(&["hello.proxy", "xrange", "stream", "-", "+"]),
+        client.cmd(&["xrange", "stream", "-", "+"])
+    );
+    assert_eq!(client.cmd(&["hello.proxy", "get", "missing"]), Value::Nil);
+    assert_eq!(client.cmd(&["hello.proxy", "xlen", "stream"]), Value::Integer(2));
+
+    assert_error(client.cmd(&["hello.proxy", "get", "stream"]), "WRONGTYPE");
+}
+
+#[test]
+fn sum_replies_with_a_big_number_past_64_bits() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let max = i64::MAX.to_string();
+    let wide = (i128::from(i64::MAX) * 2).to_string();
+    assert_eq!(client.cmd(&["hello.sum", "1", "-3"]), Value::Integer(-2));
+    assert_eq!(client.cmd(&["hello.sum", &max]), Value::Integer(i64::MAX));
+    assert_eq!(client.cmd(&["hello.sum", &max, &max]), Value::Bulk(wide.clone()));
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(client.cmd(&["hello.sum", "1", "-3"]), Value::Integer(-2));
+    assert_eq!(client.cmd(&["hello.sum", &max, &max]), Value::BigNumber(wide));
+}
+
+#[test]
+fn zhalf_reads_and_replies_doubles() {
+    let (_server, mut client) = Server::start_with_client();
+
+    client.cmd(&["zadd", "z", "3", "a"]);
+    assert_eq!(client.cmd(&["hello.zhalf", "z", "a"]), Value::Bulk("1.5".to_owned()));
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(client.cmd(&["hello.zhalf", "z", "a"]), Value::Double(1.5));
+    let message = assert_error(client.cmd(&["hello.zhalf", "z", "missing"]), "ERR");
+    assert!(message.contains("not Double"), "{}", message);
+}
+
+#[test]
+fn log_writes_percent_signs_as_is() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.log", "warning", "%s%s%n %d%%"]), Value::Status("OK".to_owned()));
+    assert_eq!(client.cmd(&["ping"]), Value::Status("PONG".to_owned()));
+}
+
+#[test]
+fn split_sets_the_array_length_it_is_given() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["hello.split", "a,bb,,c", ","]),
+        Value::Array(vec![
+            Value::Bulk("a".to_owned()),
+            Value::Bulk("bb".to_owned()),
+            Value::Bulk("".to_owned()),
+            Value::Bulk("c".to_owned()),
+        ])
+    );
+    assert_eq!(client.cmd(&["hello.split", "abc", ","]), Value::Array(vec![Value::Bulk("abc".to_owned())]));
+}
+
+#[test]
+fn bgincr_runs_jobs_on_the_worker_pool() {
+    let (_server, mut client) = Server::start_with_client();
+
+    // The panicking job still increments, and must leave the global lock
+    // and its worker usable for the jobs after it.
+    assert_eq!(client.cmd(&["hello.bgincr", "k", "panic"]), Value::Status("OK".to_owned()));
+    for _ in 0..9 {
+        assert_eq!(client.cmd(&["hello.bgincr", "k"]), Value::Status("OK".to_owned()));
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while client.cmd(&["get", "k"]) != Value::Bulk("10".to_owned()) {
+        assert!(Instant::now() < deadline, "the jobs never ran");
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn pairs_is_a_map_in_resp3_only() {
+    let (_server, mut client) = Server::start_with_client();
+
+    let args = ["hello.pairs", "a", "1", "b", "2"];
+    assert_eq!(
+        client.cmd(&args),
+        Value::Array(vec![
+            Value::Bulk("a".to_owned()),
+            Value::Bulk("1".to_owned()),
+            Value::Bulk("b".to_owned()),
+            Value::Bulk("2".to_owned()),
+        ])
+    );
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(
+        client.cmd(&args),
+        Value::Map(vec![
+            (Value::Bulk("a".to_owned()), Value::Bulk("1".to_owned())),
+            (Value::Bulk("b".to_owned()), Value::Bulk("2".to_owned())),
+        ])
+    );
+}
+
+#[test]
+fn init_receives_the_module_arguments() {
+    let server = Server::start_with_args(&["arg1", "arg2"]);
+    let mut client = server.client();
+
+    assert_eq!(
+        client.cmd(&["hello.args"]),
+        Value::Array(vec![Value::Bulk("arg1".to_owned()), Value::Bulk("arg2".to_owned())])
+    );
+}
+
+#[test]
+fn subcommands_are_dispatched() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.str", "upper", "abc"]), Value::Bulk("ABC".to_owned()));
+    assert_eq!(client.cmd(&["hello.str", "len", "abc"]), Value::Integer(3));
+}
+
+#[test]
+fn run_reply_is_sent_back() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(client.cmd(&["hello.count", "a", "b", "c"]), Value::Integer(3));
+}
+
+#[test]
+fn every_kind_of_reply_is_sent_back() {
+    let (_server, mut client) = Server::start_with_client();
+
+    assert_eq!(
+        client.cmd(&["hello.types"]),
+        Value::Array(vec![
+            Value::Integer(1),
+            Value::Bulk("two".to_owned()),
+            Value::Bulk("3.5".to_owned()),
+            Value::Integer(1),
+            Value::Nil,
+            Value::Array(vec![Value::Bulk("key".to_owned()), Value::Array(vec![Value::Integer(4)])]),
+        ])
+    );
+
+    client.cmd(&["hello", "3"]);
+    assert_eq!(
+        client.cmd(&["hello.types"]),
+        Value::Array(vec![
+            Value::Integer(1),
+            Value::Bulk("two".to_owned()),
+            Value::Double(3.5),
+            Value::Bool(true),
+            Value::Nil,
+            Value::Map(vec![(Value::Bulk("key".to_owned()), Value::Array(vec![Value::Integer(4)]))]),
+        ])
+    );
+}